@@ -0,0 +1,131 @@
+//! A logger that sends records over a channel.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::SyncSender;
+
+use {Log, Metadata, OwnedRecord, Record};
+
+/// A logger that sends an [`OwnedRecord`](struct.OwnedRecord.html) for each
+/// record it's given over a bounded channel.
+///
+/// Records are sent without blocking. If the channel is full, or its receiver
+/// has been dropped, the record is discarded and counted in
+/// [`dropped`](#method.dropped) instead.
+///
+/// Requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use log::{ChannelLogger, Level, Log, Record};
+/// use std::sync::mpsc::sync_channel;
+///
+/// let (sender, receiver) = sync_channel(16);
+/// let logger = ChannelLogger::new(sender);
+///
+/// logger.log(&Record::builder()
+///     .level(Level::Info)
+///     .args(format_args!("hello"))
+///     .build());
+///
+/// assert_eq!("hello", receiver.recv().unwrap().message());
+/// ```
+#[derive(Debug)]
+pub struct ChannelLogger {
+    sender: SyncSender<OwnedRecord>,
+    dropped: AtomicUsize,
+}
+
+impl ChannelLogger {
+    /// Create a logger that sends records to the given channel.
+    pub fn new(sender: SyncSender<OwnedRecord>) -> Self {
+        ChannelLogger {
+            sender,
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of records that couldn't be sent.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Log for ChannelLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        if self
+            .sender
+            .try_send(OwnedRecord::from_record(record))
+            .is_err()
+        {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::sync_channel;
+    use Level;
+
+    fn log(logger: &ChannelLogger, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .target("channel")
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn channel_logger_counts_dropped_records() {
+        let (sender, receiver) = sync_channel(1);
+        let logger = ChannelLogger::new(sender);
+
+        log(&logger, "first");
+        log(&logger, "second");
+
+        assert_eq!(1, logger.dropped());
+
+        let record = receiver.recv().unwrap();
+        assert_eq!(Level::Info, record.level());
+        assert_eq!("channel", record.target());
+        assert_eq!("first", record.message());
+
+        drop(receiver);
+        log(&logger, "third");
+
+        assert_eq!(2, logger.dropped());
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable")]
+    fn channel_logger_keeps_typed_values() {
+        use kv;
+        use kv::value::tests::Token;
+
+        let (sender, receiver) = sync_channel(1);
+        let logger = ChannelLogger::new(sender);
+
+        let kvs: &[(&str, kv::Value)] = &[("id", 42.into()), ("ok", true.into())];
+        logger.log(&Record::builder().key_values(&kvs).build());
+
+        let record = receiver.recv().unwrap();
+        assert_eq!(
+            Token::I64(42),
+            record.key_values().get("id".into()).unwrap().to_token()
+        );
+        assert_eq!(
+            Token::Bool(true),
+            record.key_values().get("ok".into()).unwrap().to_token()
+        );
+    }
+}
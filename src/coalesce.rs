@@ -0,0 +1,294 @@
+//! A logger that collapses repeated records.
+
+use std::sync::Mutex;
+
+use {Log, Metadata, OwnedRecord, Record};
+
+/// A logger that collapses runs of identical records.
+///
+/// The first of a run of records with the same level, target, message, and
+/// key-value pairs is forwarded to the inner logger immediately. Its repeats are
+/// counted instead of being forwarded, and when a different record arrives, or the
+/// logger is flushed, a `previous message repeated N times` record is forwarded in
+/// their place. So three identical records are forwarded as the first of them,
+/// followed by `previous message repeated 2 times`. A run of just two records isn't
+/// summarised, so the repeat is forwarded as a normal record when the run ends.
+/// Records in a run can still differ in other ways, like their location or
+/// timestamp, so a summary carries the metadata and key-value pairs of the first
+/// repeat.
+///
+/// That means the summary of a run isn't seen until the run ends, which may
+/// be some time after its last repeat was logged. Calling `flush` forces any
+/// pending summary out.
+///
+/// Records are forwarded without holding any locks, so the inner logger can log
+/// through this one again.
+///
+/// Requires the `std` feature.
+#[derive(Debug)]
+pub struct Coalesce<L> {
+    inner: L,
+    last: Mutex<Option<Last>>,
+}
+
+#[derive(Debug)]
+struct Last {
+    record: OwnedRecord,
+    repeats: usize,
+}
+
+impl Last {
+    fn matches(&self, record: &Record, message: &str) -> bool {
+        self.record.level() == record.level()
+            && self.record.target() == record.target()
+            && self.record.message() == message
+            && self.record.key_values_eq(record)
+    }
+
+    fn take_repeats(&mut self) -> Option<Last> {
+        if self.repeats == 0 {
+            return None;
+        }
+
+        let repeats = Last {
+            record: self.record.clone(),
+            repeats: self.repeats,
+        };
+        self.repeats = 0;
+
+        Some(repeats)
+    }
+}
+
+impl<L> Coalesce<L>
+where
+    L: Log,
+{
+    /// Wrap a logger.
+    pub fn new(inner: L) -> Self {
+        Coalesce {
+            inner,
+            last: Mutex::new(None),
+        }
+    }
+
+    fn log_repeats(&self, last: Last) {
+        match last.repeats {
+            0 => (),
+            1 => last.record.with_record(|record| self.inner.log(record)),
+            repeats => last
+                .record
+                .with_message(format!("previous message repeated {} times", repeats))
+                .with_record(|record| self.inner.log(record)),
+        }
+    }
+}
+
+impl<L> Log for Coalesce<L>
+where
+    L: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let message = record.args().to_string();
+
+        let pending = {
+            let mut last = self.last.lock().unwrap_or_else(|err| err.into_inner());
+
+            if let Some(ref mut last) = *last {
+                if last.matches(record, &message) {
+                    last.repeats += 1;
+
+                    // Keep the first repeat in case it needs to be forwarded as-is
+                    if last.repeats == 1 {
+                        last.record = OwnedRecord::from_record(record);
+                    }
+
+                    return;
+                }
+            }
+
+            let next = Last {
+                record: OwnedRecord::from_record(record),
+                repeats: 0,
+            };
+
+            last.replace(next).filter(|last| last.repeats > 0)
+        };
+
+        if let Some(pending) = pending {
+            self.log_repeats(pending);
+        }
+
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        let pending = self
+            .last
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_mut()
+            .and_then(Last::take_repeats);
+
+        if let Some(pending) = pending {
+            self.log_repeats(pending);
+        }
+
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Weak};
+    use Level;
+
+    struct Capture(Mutex<Vec<OwnedRecord>>);
+
+    impl Capture {
+        fn messages(&self) -> Vec<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|record| record.message().to_owned())
+                .collect()
+        }
+    }
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(OwnedRecord::from_record(record));
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn log(logger: &Coalesce<Capture>, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn coalesce_repeated_records() {
+        let logger = Coalesce::new(Capture(Mutex::new(Vec::new())));
+
+        log(&logger, "a");
+        log(&logger, "a");
+        log(&logger, "a");
+        log(&logger, "b");
+
+        assert_eq!(
+            vec!["a", "previous message repeated 2 times", "b"],
+            logger.inner.messages()
+        );
+
+        log(&logger, "b");
+        logger.flush();
+
+        assert_eq!(
+            vec!["a", "previous message repeated 2 times", "b", "b"],
+            logger.inner.messages()
+        );
+    }
+
+    #[test]
+    fn coalesce_single_repeat() {
+        let logger = Coalesce::new(Capture(Mutex::new(Vec::new())));
+
+        for line in 1..3 {
+            logger.log(
+                &Record::builder()
+                    .level(Level::Info)
+                    .args(format_args!("a"))
+                    .module_path(Some("app"))
+                    .file(Some("app.rs"))
+                    .line(Some(line))
+                    .build(),
+            );
+        }
+        log(&logger, "b");
+
+        assert_eq!(vec!["a", "a", "b"], logger.inner.messages());
+
+        let captured = logger.inner.0.lock().unwrap();
+        assert_eq!(Some("app"), captured[1].module_path());
+        assert_eq!(Some("app.rs"), captured[1].file());
+        assert_eq!(Some(2), captured[1].line());
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable")]
+    fn coalesce_compares_key_values() {
+        let logger = Coalesce::new(Capture(Mutex::new(Vec::new())));
+
+        for kvs in &[("id", 1), ("id", 1), ("id", 2)] {
+            logger.log(
+                &Record::builder()
+                    .level(Level::Info)
+                    .args(format_args!("a"))
+                    .key_values(kvs)
+                    .build(),
+            );
+        }
+
+        assert_eq!(vec!["a", "a", "a"], logger.inner.messages());
+    }
+
+    #[test]
+    fn coalesce_allows_reentrant_logging() {
+        struct Reentrant {
+            outer: Mutex<Weak<Coalesce<Reentrant>>>,
+            capture: Capture,
+        }
+
+        impl Log for Reentrant {
+            fn enabled(&self, _: &Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &Record) {
+                self.capture.log(record);
+
+                if record.args().to_string().starts_with("previous") {
+                    let outer = self.outer.lock().unwrap().upgrade().unwrap();
+                    outer.log(&Record::builder().args(format_args!("nested")).build());
+                }
+            }
+
+            fn flush(&self) {}
+        }
+
+        let logger = Arc::new(Coalesce::new(Reentrant {
+            outer: Mutex::new(Weak::new()),
+            capture: Capture(Mutex::new(Vec::new())),
+        }));
+        *logger.inner.outer.lock().unwrap() = Arc::downgrade(&logger);
+
+        for message in &["a", "a", "a"] {
+            logger.log(&Record::builder().args(format_args!("{}", message)).build());
+        }
+        logger.flush();
+
+        assert_eq!(
+            vec!["a", "previous message repeated 2 times", "nested"],
+            logger.inner.capture.messages()
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! A logger that forwards records to two loggers.
+
+use {Log, Metadata, Record};
+
+/// A logger that forwards each record to two other loggers.
+///
+/// A record is enabled if either logger enables it, and each logger is only
+/// given the records it enables itself. More than two loggers can be combined
+/// by nesting.
+///
+/// Requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use log::FanOut;
+/// # struct MyLogger;
+/// # impl log::Log for MyLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, _: &log::Record) {}
+/// #     fn flush(&self) {}
+/// # }
+///
+/// let logger = FanOut::new(MyLogger, MyLogger);
+/// ```
+#[derive(Debug)]
+pub struct FanOut<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> FanOut<A, B>
+where
+    A: Log,
+    B: Log,
+{
+    /// Forward records to both loggers, starting with `first`.
+    pub fn new(first: A, second: B) -> Self {
+        FanOut { first, second }
+    }
+}
+
+impl<A, B> Log for FanOut<A, B>
+where
+    A: Log,
+    B: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.first.enabled(metadata) || self.second.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.first.enabled(record.metadata()) {
+            self.first.log(record);
+        }
+
+        if self.second.enabled(record.metadata()) {
+            self.second.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.first.flush();
+        self.second.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use {Level, LevelFilter};
+
+    struct Capture(LevelFilter, Mutex<Vec<Level>>);
+
+    impl Log for Capture {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= self.0
+        }
+
+        fn log(&self, record: &Record) {
+            self.1.lock().unwrap().push(record.level());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn fan_out() {
+        let logger = FanOut::new(
+            Capture(LevelFilter::Warn, Mutex::new(Vec::new())),
+            Capture(LevelFilter::Info, Mutex::new(Vec::new())),
+        );
+
+        for level in &[Level::Error, Level::Info, Level::Debug] {
+            logger.log(&Record::builder().level(*level).build());
+        }
+
+        assert!(logger.enabled(&Metadata::builder().level(Level::Info).build()));
+        assert!(!logger.enabled(&Metadata::builder().level(Level::Debug).build()));
+        assert_eq!(vec![Level::Error], *logger.first.1.lock().unwrap());
+        assert_eq!(
+            vec![Level::Error, Level::Info],
+            *logger.second.1.lock().unwrap()
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! A logger that filters records with a predicate.
+
+use {Log, Metadata, Record};
+
+/// A logger that only forwards records whose metadata matches a predicate.
+///
+/// The predicate is checked before the inner logger's `enabled` check, so
+/// records it rejects are never seen by the inner logger.
+///
+/// Requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use log::Filtered;
+/// # struct MyLogger;
+/// # impl log::Log for MyLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, _: &log::Record) {}
+/// #     fn flush(&self) {}
+/// # }
+///
+/// let logger = Filtered::new(MyLogger, |metadata| metadata.target() != "noisy");
+/// ```
+#[derive(Debug)]
+pub struct Filtered<L, F> {
+    inner: L,
+    filter: F,
+}
+
+impl<L, F> Filtered<L, F>
+where
+    L: Log,
+    F: Fn(&Metadata) -> bool,
+{
+    /// Wrap a logger, forwarding records that match the given predicate.
+    pub fn new(inner: L, filter: F) -> Self {
+        Filtered { inner, filter }
+    }
+}
+
+impl<L, F> Log for Filtered<L, F>
+where
+    L: Log,
+    F: Fn(&Metadata) -> bool + Send + Sync,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        (self.filter)(metadata) && self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if (self.filter)(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use Level;
+
+    struct Capture(Mutex<Vec<String>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push(record.target().to_owned());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn filtered() {
+        let logger = Filtered::new(Capture(Mutex::new(Vec::new())), |metadata| {
+            metadata.target() != "noisy"
+        });
+
+        for target in &["app", "noisy", "db"] {
+            logger.log(&Record::builder().level(Level::Info).target(target).build());
+        }
+
+        assert!(!logger.enabled(&Metadata::builder().target("noisy").build()));
+        assert_eq!(vec!["app", "db"], *logger.inner.0.lock().unwrap());
+    }
+}
@@ -0,0 +1,89 @@
+//! A logger that flushes when it's dropped.
+
+use {Log, Metadata, Record};
+
+/// A logger that flushes another logger when it's dropped.
+///
+/// This is useful for loggers owned by a scope, like a test or a worker
+/// thread, so buffered records aren't lost when it ends. A logger passed to
+/// [`set_boxed_logger`](fn.set_boxed_logger.html) is never dropped, so call
+/// [`flush`](trait.Log.html#tymethod.flush) before exiting instead.
+///
+/// Requires the `std` feature.
+#[derive(Debug)]
+pub struct FlushOnDrop<L>
+where
+    L: Log,
+{
+    inner: L,
+}
+
+impl<L> FlushOnDrop<L>
+where
+    L: Log,
+{
+    /// Wrap a logger.
+    pub fn new(inner: L) -> Self {
+        FlushOnDrop { inner }
+    }
+}
+
+impl<L> Log for FlushOnDrop<L>
+where
+    L: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner.log(record)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+impl<L> Drop for FlushOnDrop<L>
+where
+    L: Log,
+{
+    fn drop(&mut self) {
+        self.inner.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Flushes<'a>(&'a AtomicUsize);
+
+    impl<'a> Log for Flushes<'a> {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, _: &Record) {}
+
+        fn flush(&self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn flush_on_drop() {
+        let flushes = AtomicUsize::new(0);
+
+        {
+            let logger = FlushOnDrop::new(Flushes(&flushes));
+            logger.log(&Record::builder().build());
+
+            assert_eq!(0, flushes.load(Ordering::Relaxed));
+        }
+
+        assert_eq!(1, flushes.load(Ordering::Relaxed));
+    }
+}
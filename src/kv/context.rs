@@ -0,0 +1,234 @@
+//! Contextual key-value pairs for the current thread.
+//!
+//! Pairs pushed onto the context are attached to every record logged
+//! through a [`ContextLogger`](struct.ContextLogger.html) on the same thread
+//! until they're popped again.
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use kv::source::get_default;
+use kv::value::OwnedValue;
+use kv::{Error, Key, Source, ToValue, Value, Visitor};
+use {Log, Metadata, Record};
+
+type Frame = Rc<[(String, OwnedValue)]>;
+
+thread_local! {
+    static CONTEXT: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Push a set of key-value pairs onto the current thread's context.
+///
+/// Keys are copied into owned strings and values into owned values, so the
+/// source doesn't need to outlive the call. The pairs stay in the context
+/// until a matching call to [`pop`](fn.pop.html).
+pub fn push<S>(kvs: &S)
+where
+    S: Source + ?Sized,
+{
+    struct Collect(Vec<(String, OwnedValue)>);
+
+    impl<'kvs> Visitor<'kvs> for Collect {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            self.0.push((key.to_string(), value.to_owned_value()));
+            Ok(())
+        }
+    }
+
+    let mut collect = Collect(Vec::new());
+    let _ = kvs.visit(&mut collect);
+
+    CONTEXT.with(|context| context.borrow_mut().push(Rc::from(collect.0)));
+}
+
+/// Pop the most recently pushed set of key-value pairs off the current thread's context.
+pub fn pop() {
+    CONTEXT.with(|context| {
+        context.borrow_mut().pop();
+    });
+}
+
+/// A guard that pops a set of key-value pairs off the current thread's context when dropped.
+///
+/// The guard can't be sent to another thread, because dropping it there would
+/// pop that thread's context instead.
+#[must_use]
+#[derive(Debug)]
+pub struct ContextGuard {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ContextGuard {
+    /// Push a set of key-value pairs onto the current thread's context
+    /// until the returned guard is dropped.
+    pub fn push<S>(kvs: &S) -> Self
+    where
+        S: Source + ?Sized,
+    {
+        push(kvs);
+        ContextGuard {
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        pop();
+    }
+}
+
+/// A logger that attaches the current thread's context to each record.
+///
+/// The record's own key-value pairs are visited first, followed by the
+/// context from the outermost to the innermost set of pairs.
+#[derive(Debug)]
+pub struct ContextLogger<L> {
+    inner: L,
+}
+
+impl<L> ContextLogger<L>
+where
+    L: Log,
+{
+    /// Wrap a logger.
+    pub fn new(inner: L) -> Self {
+        ContextLogger { inner }
+    }
+}
+
+impl<L> Log for ContextLogger<L>
+where
+    L: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        // Only the frames are cloned, and the borrow is released before
+        // logging so the inner logger can push onto the context
+        let frames = CONTEXT.with(|context| context.borrow().clone());
+        let context = Context(&frames);
+
+        self.inner
+            .log(&record.to_builder().chain_key_values(&context).build());
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+struct Context<'a>(&'a [Frame]);
+
+impl<'a> Source for Context<'a> {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        for frame in self.0 {
+            for (key, value) in frame.iter() {
+                visitor.visit_pair(Key::from_str(key), value.to_value())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+        get_default(self, key)
+    }
+
+    fn count(&self) -> usize {
+        self.0.iter().map(|frame| frame.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kv::source::as_map;
+    use std::sync::Mutex;
+    use Level;
+
+    struct Capture(Mutex<Vec<String>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{:?}", as_map(record.key_values())));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn context_is_attached_while_guard_is_alive() {
+        let logger = ContextLogger::new(Capture(Mutex::new(Vec::new())));
+        let log = |logger: &ContextLogger<Capture>| {
+            logger.log(
+                &Record::builder()
+                    .level(Level::Info)
+                    .key_values(&("a", 1))
+                    .build(),
+            )
+        };
+
+        {
+            let _request = ContextGuard::push(&("request", 42));
+            {
+                let _user = ContextGuard::push(&[("user", "me")] as &[_]);
+                log(&logger);
+            }
+            log(&logger);
+        }
+        log(&logger);
+
+        assert_eq!(
+            vec![
+                r#"{"a": 1, "request": 42, "user": "me"}"#,
+                r#"{"a": 1, "request": 42}"#,
+                r#"{"a": 1}"#,
+            ],
+            *logger.inner.0.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn inner_logger_can_push_context() {
+        struct Pushing {
+            capture: Capture,
+        }
+
+        impl Log for Pushing {
+            fn enabled(&self, _: &Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &Record) {
+                let _inner = ContextGuard::push(&("inner", true));
+                self.capture.log(record);
+            }
+
+            fn flush(&self) {}
+        }
+
+        let logger = ContextLogger::new(Pushing {
+            capture: Capture(Mutex::new(Vec::new())),
+        });
+
+        let _request = ContextGuard::push(&("request", "42"));
+        logger.log(&Record::builder().level(Level::Info).build());
+
+        assert_eq!(
+            vec![r#"{"request": "42"}"#],
+            *logger.inner.capture.0.lock().unwrap()
+        );
+    }
+}
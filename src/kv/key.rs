@@ -22,7 +22,10 @@ where
 
 impl<'k> ToKey for Key<'k> {
     fn to_key(&self) -> Key {
-        Key { key: self.key }
+        Key {
+            key: Inner::Borrowed(self.as_str()),
+            index: self.index,
+        }
     }
 }
 
@@ -33,32 +36,69 @@ impl ToKey for str {
 }
 
 /// A key in a structured key-value pair.
+///
+/// A key may carry the position of its pair within a source, which is set
+/// by [`source::enumerated`](source/fn.enumerated.html). The index doesn't
+/// participate in comparisons or hashing, which only consider the key itself.
 #[derive(Clone)]
 pub struct Key<'k> {
-    key: &'k str,
+    key: Inner<'k>,
+    index: Option<usize>,
+}
+
+#[derive(Clone)]
+enum Inner<'k> {
+    Borrowed(&'k str),
+    #[cfg(feature = "std")]
+    Owned(String),
 }
 
 impl<'k> Key<'k> {
     /// Get a key from a borrowed string.
     pub fn from_str(key: &'k str) -> Self {
-        Key { key: key }
+        Key {
+            key: Inner::Borrowed(key),
+            index: None,
+        }
     }
 
     /// Get a borrowed string from this key.
     pub fn as_str(&self) -> &str {
-        self.key
+        match self.key {
+            Inner::Borrowed(key) => key,
+            #[cfg(feature = "std")]
+            Inner::Owned(ref key) => key,
+        }
+    }
+
+    /// Set the position of this key's pair within its source.
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Get the position of this key's pair within its source, if it's known.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Check whether this key is equal to a string, ignoring ASCII case.
+    ///
+    /// The `PartialEq` implementation for keys is case-sensitive.
+    pub fn eq_ignore_case(&self, other: &str) -> bool {
+        self.as_str().eq_ignore_ascii_case(other)
     }
 }
 
 impl<'k> fmt::Debug for Key<'k> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.key.fmt(f)
+        self.as_str().fmt(f)
     }
 }
 
 impl<'k> fmt::Display for Key<'k> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.key.fmt(f)
+        self.as_str().fmt(f)
     }
 }
 
@@ -115,6 +155,19 @@ mod std_support {
 
     use std::borrow::Cow;
 
+    impl<'k> Key<'k> {
+        /// Get a key from an owned string.
+        ///
+        /// This is useful for keys that are computed while visiting a source,
+        /// so can't be borrowed from it.
+        pub fn from_owned(key: String) -> Self {
+            Key {
+                key: Inner::Owned(key),
+                index: None,
+            }
+        }
+    }
+
     impl ToKey for String {
         fn to_key(&self) -> Key {
             Key::from_str(self)
@@ -138,7 +191,7 @@ mod sval_support {
 
     impl<'a> Value for Key<'a> {
         fn stream(&self, stream: &mut value::Stream) -> value::Result {
-            self.key.stream(stream)
+            self.as_str().stream(stream)
         }
     }
 }
@@ -156,7 +209,7 @@ mod serde_support {
         where
             S: Serializer,
         {
-            self.key.serialize(serializer)
+            self.as_str().serialize(serializer)
         }
     }
 }
@@ -169,4 +222,28 @@ mod tests {
     fn key_from_string() {
         assert_eq!("a key", Key::from_str("a key").as_str());
     }
+
+    #[test]
+    fn key_with_index() {
+        assert_eq!(None, Key::from_str("a key").index());
+
+        let key = Key::from_str("a key").with_index(1);
+        assert_eq!(Some(1), key.index());
+        assert_eq!(Some(1), key.to_key().index());
+        assert_eq!(Key::from_str("a key"), key);
+    }
+
+    #[test]
+    fn key_eq_ignore_case() {
+        assert!(Key::from_str("User_Id").eq_ignore_case("user_id"));
+        assert!(!Key::from_str("User_Id").eq_ignore_case("user"));
+        assert_ne!(Key::from_str("User_Id"), Key::from_str("user_id"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn key_from_owned() {
+        assert_eq!("a key", Key::from_owned("a key".to_owned()).as_str());
+        assert_eq!(Key::from_str("a key"), Key::from_owned("a key".to_owned()));
+    }
 }
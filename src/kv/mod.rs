@@ -12,6 +12,8 @@
 //! features = ["kv_unstable"]
 //! ```
 
+#[cfg(feature = "std")]
+pub mod context;
 mod error;
 mod key;
 pub mod source;
@@ -23,4 +25,36 @@ pub use self::key::{Key, ToKey};
 pub use self::source::{Source, Visitor};
 
 #[doc(inline)]
-pub use self::value::{ToValue, Value};
+pub use self::value::{FromValue, ToValue, Value};
+
+/// Whether structured values can be serialized with `serde`.
+///
+/// This is `true` when the `kv_unstable_serde` feature is enabled. Without it,
+/// backends that would otherwise serialize values fall back to their `Debug` form,
+/// so applications can assert this at startup to catch mismatched builds.
+pub const fn serde_support_enabled() -> bool {
+    cfg!(feature = "kv_unstable_serde")
+}
+
+/// Whether structured values can be streamed with `sval`.
+///
+/// This is `true` when the `kv_unstable_sval` feature is enabled. Without it,
+/// backends that would otherwise stream values fall back to their `Debug` form,
+/// so applications can assert this at startup to catch mismatched builds.
+pub const fn sval_support_enabled() -> bool {
+    cfg!(feature = "kv_unstable_sval")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn support_enabled_reflects_features() {
+        const SERDE: bool = serde_support_enabled();
+        const SVAL: bool = sval_support_enabled();
+
+        assert_eq!(cfg!(feature = "kv_unstable_serde"), SERDE);
+        assert_eq!(cfg!(feature = "kv_unstable_sval"), SVAL);
+    }
+}
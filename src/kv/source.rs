@@ -6,9 +6,22 @@ extern crate sval;
 #[cfg(feature = "kv_unstable_serde")]
 extern crate serde;
 
-use kv::{Error, Key, ToKey, ToValue, Value};
+use kv::{Error, FromValue, Key, ToKey, ToValue, Value};
 use std::fmt;
 
+#[cfg(feature = "kv_unstable_serde")]
+pub use self::serde_support::{from_serialize_flat, SerializeFlat};
+#[cfg(all(feature = "std", any(test, feature = "test-util")))]
+pub use self::std_support::check_source_consistency;
+#[cfg(feature = "std")]
+pub use self::std_support::{
+    by_priority, cap_keys, catch_errors, collect_map, collect_strings_into, debug_dump,
+    escape_gelf, escape_syslog_sd, fields_hash, flatten, from_display_keys, from_env_prefix,
+    group_by_key, numeric_fields, ordered_by, parse_numeric_keys, stringify_values, visit_timed,
+    CapKeys, CaughtErrors, CollectVisitor, DisplayKeys, EnvSource, EscapingVisitor, Flatten,
+    Grouped, HostInfo, MutexSource, OrderedBy, ParseNumericKeys, StringifyValues,
+};
+
 /// A source of key-value pairs.
 ///
 /// The source may be a single pair, a set of pairs, or a filter over a set of pairs.
@@ -52,7 +65,10 @@ pub trait Source {
     /// efficient implementation.
     ///
     /// A subsequent call to `visit` should yield the same number of key-value pairs
-    /// to the visitor, unless that visitor fails part way through.
+    /// to the visitor, unless that visitor fails part way through. The count is
+    /// announced to serializers as the length of a map or sequence before any
+    /// pairs are visited, so sources whose pairs can change between calls
+    /// shouldn't return a stale count.
     #[cfg(not(test))]
     fn count(&self) -> usize {
         count_default(self)
@@ -85,6 +101,115 @@ pub(crate) fn get_default<'v>(source: &'v (impl Source + ?Sized), key: Key) -> O
     get.found
 }
 
+/// Get the value for a given key, ignoring ASCII case.
+///
+/// Unlike [`Source::get`](trait.Source.html#method.get), which matches keys exactly,
+/// this visits the source and returns the first value whose key is equal to `key`
+/// using [`Key::eq_ignore_case`](../struct.Key.html#method.eq_ignore_case).
+pub fn get_ignore_case<'v, S>(source: &'v S, key: &str) -> Option<Value<'v>>
+where
+    S: Source + ?Sized,
+{
+    struct GetIgnoreCase<'k, 'v> {
+        key: &'k str,
+        found: Option<Value<'v>>,
+    }
+
+    impl<'k, 'kvs> Visitor<'kvs> for GetIgnoreCase<'k, 'kvs> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            if self.found.is_none() && key.eq_ignore_case(self.key) {
+                self.found = Some(value);
+            }
+
+            Ok(())
+        }
+    }
+
+    let mut get = GetIgnoreCase { key, found: None };
+
+    let _ = source.visit(&mut get);
+    get.found
+}
+
+/// Get the value for a given key as a concrete type.
+///
+/// This is a combination of [`Source::get`](trait.Source.html#method.get)
+/// and [`FromValue::from_value`](../value/trait.FromValue.html#tymethod.from_value).
+/// If the key isn't found, or its value can't be converted into `T`, then
+/// `None` is returned.
+pub fn get_as<S, Q, T>(source: &S, key: Q) -> Option<T>
+where
+    S: Source + ?Sized,
+    Q: ToKey,
+    T: FromValue,
+{
+    source
+        .get(key.to_key())
+        .and_then(|value| T::from_value(&value))
+}
+
+/// Get the first key-value pair in a source.
+///
+/// "First" follows the order the source visits its pairs in, which isn't
+/// necessarily the order they were added in. The visit is stopped as soon
+/// as a pair is found.
+pub fn first<'kvs, S>(source: &'kvs S) -> Option<(Key<'kvs>, Value<'kvs>)>
+where
+    S: Source + ?Sized,
+{
+    struct First<'kvs>(Option<(Key<'kvs>, Value<'kvs>)>);
+
+    impl<'kvs> Visitor<'kvs> for First<'kvs> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            self.0 = Some((key, value));
+
+            // Stop visiting once we've got a pair
+            Err(Error::msg("found the first pair"))
+        }
+    }
+
+    let mut first = First(None);
+
+    let _ = source.visit(&mut first);
+    first.0
+}
+
+/// Get the first non-`None` result of calling `f` on the pairs in a source.
+///
+/// This is the `Source` equivalent of `Iterator::find_map`. The visit is
+/// stopped as soon as `f` returns `Some`, so any remaining pairs aren't visited.
+pub fn find_map<'kvs, S, T, F>(source: &'kvs S, f: F) -> Option<T>
+where
+    S: Source + ?Sized,
+    F: FnMut(Key<'kvs>, Value<'kvs>) -> Option<T>,
+{
+    struct FindMap<T, F> {
+        f: F,
+        found: Option<T>,
+    }
+
+    impl<'kvs, T, F> Visitor<'kvs> for FindMap<T, F>
+    where
+        F: FnMut(Key<'kvs>, Value<'kvs>) -> Option<T>,
+    {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            self.found = (self.f)(key, value);
+
+            if self.found.is_some() {
+                // Stop visiting once we've got a result
+                Err(Error::msg("found a result"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    let mut find_map = FindMap { f, found: None };
+
+    let _ = source.visit(&mut find_map);
+    find_map.found
+}
+
 /// The default implementation of `Source::count`.
 pub(crate) fn count_default(source: impl Source) -> usize {
     struct Count(usize);
@@ -102,6 +227,26 @@ pub(crate) fn count_default(source: impl Source) -> usize {
     count.0
 }
 
+/// Visit a source once, buffering its pairs.
+#[cfg(feature = "std")]
+pub(crate) fn buffer_pairs<'kvs, S>(source: &'kvs S) -> Result<Vec<(Key<'kvs>, Value<'kvs>)>, Error>
+where
+    S: Source + ?Sized,
+{
+    struct Buffer<'kvs>(Vec<(Key<'kvs>, Value<'kvs>)>);
+
+    impl<'kvs> Visitor<'kvs> for Buffer<'kvs> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            self.0.push((key, value));
+            Ok(())
+        }
+    }
+
+    let mut buffer = Buffer(Vec::new());
+    source.visit(&mut buffer)?;
+    Ok(buffer.0)
+}
+
 impl<'a, T> Source for &'a T
 where
     T: Source + ?Sized,
@@ -164,7 +309,7 @@ where
     }
 
     fn count(&self) -> usize {
-        self.len()
+        self.iter().map(Source::count).sum()
     }
 }
 
@@ -233,187 +378,2190 @@ impl<'a, 'b: 'a, 'kvs> Visitor<'kvs> for fmt::DebugTuple<'a, 'b> {
     }
 }
 
-#[cfg(feature = "std")]
-mod std_support {
-    use super::*;
-    use std::borrow::Borrow;
-    use std::collections::{BTreeMap, HashMap};
-    use std::hash::{BuildHasher, Hash};
+/// The result of calling `source::tee`.
+pub struct Tee<'a, 'kvs: 'a> {
+    a: &'a mut dyn Visitor<'kvs>,
+    b: &'a mut dyn Visitor<'kvs>,
+}
 
-    impl<S> Source for Box<S>
-    where
-        S: Source + ?Sized,
-    {
-        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
-            Source::visit(&**self, visitor)
-        }
+/// Drive two visitors with a single visit of a source.
+///
+/// Each key-value pair is passed to `a` and then to `b`. Both visitors see every pair,
+/// even if one of them fails, and the first error is returned.
+pub fn tee<'a, 'kvs>(a: &'a mut dyn Visitor<'kvs>, b: &'a mut dyn Visitor<'kvs>) -> Tee<'a, 'kvs> {
+    Tee { a, b }
+}
 
-        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
-            Source::get(&**self, key)
-        }
+impl<'a, 'kvs> Visitor<'kvs> for Tee<'a, 'kvs> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        let a = self.a.visit_pair(key.clone(), value.clone());
+        let b = self.b.visit_pair(key, value);
 
-        fn count(&self) -> usize {
-            Source::count(&**self)
-        }
+        a.and(b)
     }
+}
 
-    impl<S> Source for Vec<S>
-    where
-        S: Source,
-    {
-        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
-            Source::visit(&**self, visitor)
-        }
+impl<'a, 'kvs> fmt::Debug for Tee<'a, 'kvs> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tee").finish()
+    }
+}
 
-        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
-            Source::get(&**self, key)
-        }
+/// A visitor that checks each key before forwarding it to another visitor.
+///
+/// If a key doesn't satisfy the predicate then visiting stops with an error.
+/// This can surface schema violations for sinks with strict rules for keys,
+/// like not allowing spaces or dots, rather than producing corrupt output.
+pub struct ValidatingVisitor<'a, 'kvs: 'a, F> {
+    visitor: &'a mut dyn Visitor<'kvs>,
+    validate: F,
+}
 
-        fn count(&self) -> usize {
-            Source::count(&**self)
-        }
+impl<'a, 'kvs, F> ValidatingVisitor<'a, 'kvs, F>
+where
+    F: Fn(&str) -> bool,
+{
+    /// Wrap a visitor, checking each key with `validate`.
+    pub fn new(visitor: &'a mut dyn Visitor<'kvs>, validate: F) -> Self {
+        ValidatingVisitor { visitor, validate }
     }
+}
 
-    impl<'kvs, V> Visitor<'kvs> for Box<V>
-    where
-        V: Visitor<'kvs> + ?Sized,
-    {
-        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
-            (**self).visit_pair(key, value)
+impl<'a, 'kvs, F> Visitor<'kvs> for ValidatingVisitor<'a, 'kvs, F>
+where
+    F: Fn(&str) -> bool,
+{
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        if !(self.validate)(key.as_str()) {
+            return Err(Error::msg("a key failed validation"));
         }
+
+        self.visitor.visit_pair(key, value)
     }
+}
 
-    impl<K, V, S> Source for HashMap<K, V, S>
-    where
-        K: ToKey + Borrow<str> + Eq + Hash,
-        V: ToValue,
-        S: BuildHasher,
-    {
-        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
-            for (key, value) in self {
-                visitor.visit_pair(key.to_key(), value.to_value())?;
-            }
+impl<'a, 'kvs, F> fmt::Debug for ValidatingVisitor<'a, 'kvs, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ValidatingVisitor").finish()
+    }
+}
+
+/// Check every key in a source against a predicate.
+///
+/// An error is returned for the first key that doesn't satisfy `validate`.
+pub fn validate_keys<S, F>(source: S, validate: F) -> Result<(), Error>
+where
+    S: Source,
+    F: Fn(&str) -> bool,
+{
+    struct Ignore;
+
+    impl<'kvs> Visitor<'kvs> for Ignore {
+        fn visit_pair(&mut self, _: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
             Ok(())
         }
+    }
 
-        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
-            HashMap::get(self, key.as_str()).map(|v| v.to_value())
-        }
+    source.visit(&mut ValidatingVisitor::new(&mut Ignore, validate))
+}
 
-        fn count(&self) -> usize {
-            self.len()
-        }
+/// A source that layers per-record pairs over a long-lived base context.
+///
+/// Pairs in `extra` override pairs in `base` with the same key when calling `get`,
+/// which is forwarded to each source in turn so any efficient lookup they
+/// implement is kept. Visiting produces the pairs in `extra` followed by the
+/// pairs in `base`, including any that are overridden.
+#[derive(Clone, Copy)]
+pub struct ContextOverlay<'a> {
+    base: &'a dyn Source,
+    extra: &'a dyn Source,
+}
+
+impl<'a> ContextOverlay<'a> {
+    /// Layer `extra` over `base`.
+    pub fn new(base: &'a dyn Source, extra: &'a dyn Source) -> Self {
+        ContextOverlay { base, extra }
     }
+}
 
-    impl<K, V> Source for BTreeMap<K, V>
-    where
-        K: ToKey + Borrow<str> + Ord,
-        V: ToValue,
-    {
-        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
-            for (key, value) in self {
-                visitor.visit_pair(key.to_key(), value.to_value())?;
-            }
-            Ok(())
-        }
+impl<'a> Source for ContextOverlay<'a> {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        self.extra.visit(visitor)?;
+        self.base.visit(visitor)
+    }
 
-        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
-            BTreeMap::get(self, key.as_str()).map(|v| v.to_value())
-        }
+    fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+        self.extra.get(key.clone()).or_else(|| self.base.get(key))
+    }
 
-        fn count(&self) -> usize {
-            self.len()
-        }
+    fn count(&self) -> usize {
+        self.extra.count() + self.base.count()
     }
+}
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use kv::value::tests::Token;
-        use std::collections::{BTreeMap, HashMap};
+impl<'a> fmt::Debug for ContextOverlay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ContextOverlay")
+            .field("base", &as_map(self.base))
+            .field("extra", &as_map(self.extra))
+            .finish()
+    }
+}
 
-        #[test]
-        fn count() {
-            assert_eq!(1, Source::count(&Box::new(("a", 1))));
-            assert_eq!(2, Source::count(&vec![("a", 1), ("b", 2)]));
-        }
+/// A visitor that counts the bytes a source would take up as text.
+///
+/// Pairs are measured as if they were written as `key=value`, with the value
+/// formatted using its `Display` implementation, and separated by a single space.
+/// Nothing is allocated while measuring, so this can be used to budget log output
+/// without actually writing it. The size is an estimate, since a writer may
+/// quote or escape values.
+#[derive(Debug, Default)]
+pub struct SizeVisitor {
+    size: usize,
+    pairs: usize,
+}
 
-        #[test]
-        fn get() {
-            let source = vec![("a", 1), ("b", 2), ("a", 1)];
-            assert_eq!(
-                Token::I64(1),
-                Source::get(&source, Key::from_str("a")).unwrap().to_token()
-            );
+impl SizeVisitor {
+    /// Create a visitor with a size of `0`.
+    pub fn new() -> Self {
+        SizeVisitor::default()
+    }
 
-            let source = Box::new(Option::None::<(&str, i32)>);
-            assert!(Source::get(&source, Key::from_str("a")).is_none());
-        }
+    /// The size, in bytes, of the pairs visited so far.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
 
-        #[test]
-        fn hash_map() {
-            let mut map = HashMap::new();
-            map.insert("a", 1);
-            map.insert("b", 2);
+impl<'kvs> Visitor<'kvs> for SizeVisitor {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        struct Count(usize);
 
-            assert_eq!(2, Source::count(&map));
-            assert_eq!(
-                Token::I64(1),
-                Source::get(&map, Key::from_str("a")).unwrap().to_token()
-            );
+        impl fmt::Write for Count {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0 += s.len();
+                Ok(())
+            }
         }
 
-        #[test]
-        fn btree_map() {
-            let mut map = BTreeMap::new();
-            map.insert("a", 1);
-            map.insert("b", 2);
+        let mut value_size = Count(0);
+        fmt::write(&mut value_size, format_args!("{}", value))?;
 
-            assert_eq!(2, Source::count(&map));
-            assert_eq!(
-                Token::I64(1),
-                Source::get(&map, Key::from_str("a")).unwrap().to_token()
-            );
+        if self.pairs > 0 {
+            // The space between this pair and the last one
+            self.size += 1;
         }
+
+        // The key, the `=`, and the value
+        self.size += key.as_str().len() + 1 + value_size.0;
+        self.pairs += 1;
+
+        Ok(())
     }
 }
 
-/// The result of calling `Source::as_map`.
-pub struct AsMap<S>(S);
-
-/// Visit this source as a map.
-pub fn as_map<S>(source: S) -> AsMap<S>
+/// Estimate the number of bytes a source would take up as text.
+///
+/// See [`SizeVisitor`](struct.SizeVisitor.html) for the format being measured.
+pub fn estimated_text_size<S>(source: S) -> usize
 where
     S: Source,
 {
-    AsMap(source)
+    let mut size = SizeVisitor::new();
+    let _ = source.visit(&mut size);
+
+    size.size()
 }
 
-impl<S> Source for AsMap<S>
+/// The result of calling `source::lookup`.
+pub struct LookupSource<F>(F);
+
+/// A source that looks up values on demand.
+///
+/// This source only supports `get`, which calls the given function with the key
+/// to resolve. It's useful when values live in an external store where point lookups
+/// are cheap, but listing every key isn't. Visiting this source produces no pairs,
+/// and its count is always `0`.
+pub fn lookup<F>(lookup: F) -> LookupSource<F>
 where
-    S: Source,
+    F: Fn(&str) -> Option<Value<'static>>,
 {
-    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
-        self.0.visit(visitor)
+    LookupSource(lookup)
+}
+
+impl<F> Source for LookupSource<F>
+where
+    F: Fn(&str) -> Option<Value<'static>>,
+{
+    fn visit<'kvs>(&'kvs self, _: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        Ok(())
     }
 
     fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
-        self.0.get(key)
+        (self.0)(key.as_str())
     }
 
     fn count(&self) -> usize {
-        self.0.count()
+        0
     }
 }
 
-impl<S> fmt::Debug for AsMap<S>
-where
-    S: Source,
-{
+impl<F> fmt::Debug for LookupSource<F> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut f = f.debug_map();
-        self.0.visit(&mut f).map_err(|_| fmt::Error)?;
-        f.finish()
+        f.debug_struct("LookupSource").finish()
+    }
+}
+
+/// The result of calling `source::retain_keys`.
+#[derive(Debug)]
+pub struct RetainKeys<S> {
+    source: S,
+    allow: &'static [&'static str],
+}
+
+/// Keep only the pairs in this source whose keys are in `allow`.
+///
+/// This is the inverse of filtering out unwanted keys, and is useful for
+/// producing a minimal schema. Keys are matched exactly. Any other pairs are
+/// skipped during visitation and aren't returned from `get`.
+pub fn retain_keys<S>(source: S, allow: &'static [&'static str]) -> RetainKeys<S>
+where
+    S: Source,
+{
+    RetainKeys { source, allow }
+}
+
+impl<S> Source for RetainKeys<S>
+where
+    S: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        struct RetainVisitor<'a, 'kvs: 'a> {
+            allow: &'static [&'static str],
+            visitor: &'a mut dyn Visitor<'kvs>,
+        }
+
+        impl<'a, 'kvs> Visitor<'kvs> for RetainVisitor<'a, 'kvs> {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                if self.allow.contains(&key.as_str()) {
+                    self.visitor.visit_pair(key, value)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+
+        self.source.visit(&mut RetainVisitor {
+            allow: self.allow,
+            visitor,
+        })
+    }
+
+    fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+        if self.allow.contains(&key.as_str()) {
+            self.source.get(key)
+        } else {
+            None
+        }
+    }
+
+    fn count(&self) -> usize {
+        count_default(self)
+    }
+}
+
+/// The result of calling `source::enumerated`.
+#[derive(Debug)]
+pub struct Enumerated<S>(S);
+
+/// Set the index of each key in this source to its position.
+///
+/// Indices start from `0`, and follow the order the source visits its pairs in.
+/// They're available from [`Key::index`](../struct.Key.html#method.index), so
+/// consumers can refer to pairs by their position.
+pub fn enumerated<S>(source: S) -> Enumerated<S>
+where
+    S: Source,
+{
+    Enumerated(source)
+}
+
+impl<S> Source for Enumerated<S>
+where
+    S: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        struct EnumeratingVisitor<'a, 'kvs: 'a> {
+            index: usize,
+            visitor: &'a mut dyn Visitor<'kvs>,
+        }
+
+        impl<'a, 'kvs> Visitor<'kvs> for EnumeratingVisitor<'a, 'kvs> {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                let index = self.index;
+                self.index += 1;
+
+                self.visitor.visit_pair(key.with_index(index), value)
+            }
+        }
+
+        self.0.visit(&mut EnumeratingVisitor { index: 0, visitor })
+    }
+
+    fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+        self.0.get(key)
+    }
+
+    fn count(&self) -> usize {
+        self.0.count()
+    }
+}
+
+/// The result of calling `source::with_default`.
+#[derive(Debug)]
+pub struct WithDefault<S, V> {
+    source: S,
+    key: &'static str,
+    value: V,
+}
+
+/// Add a pair to this source, unless it already has a value for `key`.
+///
+/// This is useful for fields like `region=unknown` that should always be
+/// present. Every pair in the source is visited first, then the default pair
+/// is visited if `source.get(key)` found nothing. That means visiting,
+/// and counting, performs an extra `get` on the source to check whether the
+/// key is present, which may be a full scan of its pairs.
+pub fn with_default<S, V>(source: S, key: &'static str, value: V) -> WithDefault<S, V>
+where
+    S: Source,
+    V: ToValue,
+{
+    WithDefault { source, key, value }
+}
+
+impl<S, V> WithDefault<S, V>
+where
+    S: Source,
+{
+    fn is_missing(&self) -> bool {
+        self.source.get(Key::from_str(self.key)).is_none()
+    }
+}
+
+impl<S, V> Source for WithDefault<S, V>
+where
+    S: Source,
+    V: ToValue,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        self.source.visit(visitor)?;
+
+        if self.is_missing() {
+            visitor.visit_pair(Key::from_str(self.key), self.value.to_value())?;
+        }
+
+        Ok(())
+    }
+
+    fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+        let is_default = key.as_str() == self.key;
+
+        self.source.get(key).or_else(|| {
+            if is_default {
+                Some(self.value.to_value())
+            } else {
+                None
+            }
+        })
+    }
+
+    fn count(&self) -> usize {
+        if self.is_missing() {
+            self.source.count() + 1
+        } else {
+            self.source.count()
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_support {
+    use super::*;
+    use kv::value::OwnedValue;
+    use std::borrow::{Borrow, Cow};
+    use std::cmp::Ordering;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{BTreeMap, HashMap};
+    use std::hash::{BuildHasher, Hash, Hasher};
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+    use std::{env, fs, mem, process};
+
+    impl<S> Source for Box<S>
+    where
+        S: Source + ?Sized,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            Source::visit(&**self, visitor)
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            Source::get(&**self, key)
+        }
+
+        fn count(&self) -> usize {
+            Source::count(&**self)
+        }
+    }
+
+    impl<S> Source for Vec<S>
+    where
+        S: Source,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            Source::visit(&**self, visitor)
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            Source::get(&**self, key)
+        }
+
+        fn count(&self) -> usize {
+            Source::count(&**self)
+        }
+    }
+
+    impl<'kvs, V> Visitor<'kvs> for Box<V>
+    where
+        V: Visitor<'kvs> + ?Sized,
+    {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            (**self).visit_pair(key, value)
+        }
+    }
+
+    impl<K, V, S> Source for HashMap<K, V, S>
+    where
+        K: ToKey + Borrow<str> + Eq + Hash,
+        V: ToValue,
+        S: BuildHasher,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            for (key, value) in self {
+                visitor.visit_pair(key.to_key(), value.to_value())?;
+            }
+            Ok(())
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            HashMap::get(self, key.as_str()).map(|v| v.to_value())
+        }
+
+        fn count(&self) -> usize {
+            self.len()
+        }
+    }
+
+    impl<K, V> Source for BTreeMap<K, V>
+    where
+        K: ToKey + Borrow<str> + Ord,
+        V: ToValue,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            for (key, value) in self {
+                visitor.visit_pair(key.to_key(), value.to_value())?;
+            }
+            Ok(())
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            BTreeMap::get(self, key.as_str()).map(|v| v.to_value())
+        }
+
+        fn count(&self) -> usize {
+            self.len()
+        }
+    }
+
+    /// Collect the key-value pairs in a source into a map of owned values.
+    ///
+    /// Each value is captured as an [`OwnedValue`](../value/struct.OwnedValue.html),
+    /// so the map doesn't borrow from the source but primitives keep their type.
+    /// The map is itself a `Source`, so collected pairs can be attached to other
+    /// records later. If a key appears multiple times then the last value for it
+    /// is kept.
+    pub fn collect_map<S>(source: S) -> BTreeMap<String, OwnedValue>
+    where
+        S: Source,
+    {
+        struct Collect(BTreeMap<String, OwnedValue>);
+
+        impl<'kvs> Visitor<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.insert(key.to_string(), value.to_owned_value());
+                Ok(())
+            }
+        }
+
+        let mut collect = Collect(BTreeMap::new());
+        let _ = source.visit(&mut collect);
+
+        collect.0
+    }
+
+    /// A visitor that pushes rendered keys and values onto an existing `Vec`.
+    ///
+    /// Values are rendered using their `Display` implementation. Reusing the
+    /// same `Vec` across calls avoids allocating a new one for each source.
+    #[derive(Debug)]
+    pub struct CollectVisitor<'a>(&'a mut Vec<(String, String)>);
+
+    impl<'a> CollectVisitor<'a> {
+        /// Collect pairs onto the end of `out`.
+        pub fn new(out: &'a mut Vec<(String, String)>) -> Self {
+            CollectVisitor(out)
+        }
+    }
+
+    impl<'a, 'kvs> Visitor<'kvs> for CollectVisitor<'a> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            self.0.push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+
+    /// Push the rendered key-value pairs in a source onto the end of `out`.
+    ///
+    /// This uses a [`CollectVisitor`](struct.CollectVisitor.html), so values are
+    /// rendered using their `Display` implementation.
+    pub fn collect_strings_into<S>(source: S, out: &mut Vec<(String, String)>)
+    where
+        S: Source,
+    {
+        let _ = source.visit(&mut CollectVisitor::new(out));
+    }
+
+    /// Render the key-value pairs in a source as one `key = value` line per pair.
+    ///
+    /// Lines are sorted by key, and values are rendered using their `Debug`
+    /// implementation, so the output is stable regardless of the order the
+    /// source visits its pairs in. This is intended for test failures and
+    /// debugging rather than for writing log output. Pairs with the same key
+    /// keep the order they were visited in.
+    pub fn debug_dump<S>(source: S) -> String
+    where
+        S: Source,
+    {
+        struct Collect(Vec<(String, String)>);
+
+        impl<'kvs> Visitor<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.push((key.to_string(), format!("{:?}", value)));
+                Ok(())
+            }
+        }
+
+        let mut collect = Collect(Vec::new());
+        let _ = source.visit(&mut collect);
+
+        collect.0.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut dump = String::new();
+        for (key, value) in collect.0 {
+            dump.push_str(&key);
+            dump.push_str(" = ");
+            dump.push_str(&value);
+            dump.push('\n');
+        }
+
+        dump
+    }
+
+    /// Hash the key-value pairs in a source.
+    ///
+    /// Pairs are sorted by key and rendered value before they're hashed, so sources
+    /// with the same pairs in different orders hash the same. Values are hashed
+    /// using their `Display` implementation, so `1` and `"1"` hash the same too.
+    ///
+    /// The hash is computed with the standard library's `DefaultHasher`, so it's
+    /// only stable within a single build of a program. It shouldn't be persisted.
+    pub fn fields_hash<S>(source: S) -> u64
+    where
+        S: Source,
+    {
+        let mut pairs = Vec::new();
+        collect_strings_into(source, &mut pairs);
+
+        pairs.sort();
+
+        let mut hasher = DefaultHasher::new();
+        pairs.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Collect the key-value pairs in a source whose values are numbers.
+    ///
+    /// Integer and float values are converted into `f64`, so very large integers
+    /// may lose precision. Any other values, including strings that look like
+    /// numbers, are skipped. Pairs are returned in the order they were visited.
+    pub fn numeric_fields<S>(source: S) -> Vec<(String, f64)>
+    where
+        S: Source,
+    {
+        struct Collect(Vec<(String, f64)>);
+
+        impl<'kvs> Visitor<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                let number = value
+                    .to_f64()
+                    .or_else(|| value.to_i64().map(|v| v as f64))
+                    .or_else(|| value.to_u64().map(|v| v as f64));
+
+                if let Some(number) = number {
+                    self.0.push((key.to_string(), number));
+                }
+
+                Ok(())
+            }
+        }
+
+        let mut collect = Collect(Vec::new());
+        let _ = source.visit(&mut collect);
+
+        collect.0
+    }
+
+    /// A source of the pairs in a map shared behind a `Mutex`.
+    ///
+    /// Each visit clones the pairs out of the map under a single lock, and then
+    /// visits that snapshot after the lock is released. Visitors always see a
+    /// consistent set of pairs, and they can log or update the map themselves
+    /// without deadlocking. If the mutex is poisoned then the map is still visited.
+    ///
+    /// `get` and `count` take the lock again, so they can disagree with a visit
+    /// that happens while the map is being updated. Serializing a source with
+    /// [`as_map`](fn.as_map.html) uses the visited pairs for its length rather
+    /// than `count`.
+    ///
+    /// Maps like this can be built with [`collect_map`](fn.collect_map.html).
+    #[derive(Debug)]
+    pub struct MutexSource<'a>(&'a Mutex<BTreeMap<String, OwnedValue>>);
+
+    impl<'a> MutexSource<'a> {
+        /// Visit the pairs in a shared map.
+        pub fn new(map: &'a Mutex<BTreeMap<String, OwnedValue>>) -> Self {
+            MutexSource(map)
+        }
+    }
+
+    impl<'a> Source for MutexSource<'a> {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            let snapshot = self.0.lock().unwrap_or_else(|err| err.into_inner()).clone();
+
+            for (key, value) in snapshot {
+                visitor.visit_pair(Key::from_owned(key), Value::from_owned(value))?;
+            }
+
+            Ok(())
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            self.0
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .get(key.as_str())
+                .map(|value| Value::from_owned(value.clone()))
+        }
+
+        fn count(&self) -> usize {
+            self.0.lock().unwrap_or_else(|err| err.into_inner()).len()
+        }
+    }
+
+    /// Visit key-value pairs, measuring how long the visit takes.
+    ///
+    /// This is a diagnostic tool for profiling the overhead of structured logging.
+    /// The returned `Duration` covers the whole call to `Source::visit`, including
+    /// time spent in the visitor itself.
+    pub fn visit_timed<'kvs, S>(
+        source: &'kvs S,
+        visitor: &mut dyn Visitor<'kvs>,
+    ) -> (Result<(), Error>, Duration)
+    where
+        S: Source + ?Sized,
+    {
+        let start = Instant::now();
+        let result = source.visit(visitor);
+
+        (result, start.elapsed())
+    }
+
+    /// The result of calling `source::flatten`.
+    #[derive(Debug)]
+    pub struct Flatten<S>(S);
+
+    /// Flatten nested sources into this one.
+    ///
+    /// Any value created with [`Value::from_source`](../value/struct.Value.html#method.from_source)
+    /// is expanded into its own key-value pairs, with keys joined to the key of
+    /// the value they were nested in by a `.`. So a value `{ "b": 1 }` under the key `a`
+    /// is visited as `a.b = 1`.
+    pub fn flatten<S>(source: S) -> Flatten<S>
+    where
+        S: Source,
+    {
+        Flatten(source)
+    }
+
+    impl<S> Source for Flatten<S>
+    where
+        S: Source,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            self.0.visit(&mut FlattenVisitor {
+                prefix: None,
+                visitor,
+            })
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            get_default(self, key)
+        }
+
+        fn count(&self) -> usize {
+            count_default(self)
+        }
+    }
+
+    /// The result of calling `source::from_display_keys`.
+    #[derive(Debug)]
+    pub struct DisplayKeys<'a, M: 'a>(&'a M);
+
+    /// Visit a map whose keys aren't strings, like `HashMap<u32, V>`.
+    ///
+    /// Each key is formatted using its `Display` implementation into an owned string
+    /// while the map is visited, so this allocates for every pair visited.
+    pub fn from_display_keys<'a, M>(map: &'a M) -> DisplayKeys<'a, M> {
+        DisplayKeys(map)
+    }
+
+    impl<'a, M, K, V> Source for DisplayKeys<'a, M>
+    where
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: fmt::Display + 'a,
+        V: ToValue + 'a,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            for (key, value) in self.0 {
+                visitor.visit_pair(Key::from_owned(key.to_string()), value.to_value())?;
+            }
+            Ok(())
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            get_default(self, key)
+        }
+
+        fn count(&self) -> usize {
+            self.0.into_iter().count()
+        }
+    }
+
+    /// The result of calling `source::group_by_key`.
+    #[derive(Debug)]
+    pub struct Grouped<S>(S);
+
+    /// Combine the values of repeated keys instead of keeping only one of them.
+    ///
+    /// Each key is visited once, in the order it first appears in the source.
+    /// A key that appears once keeps its value as-is. A key that appears multiple
+    /// times is visited with a sequence of all its values, so three `tag` pairs
+    /// become a single `tag = ["a", "b", "c"]`.
+    ///
+    /// The whole source is buffered while it's visited.
+    pub fn group_by_key<S>(source: S) -> Grouped<S>
+    where
+        S: Source,
+    {
+        Grouped(source)
+    }
+
+    impl<S> Source for Grouped<S>
+    where
+        S: Source,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            struct Group<'kvs>(Vec<(Key<'kvs>, Vec<Value<'kvs>>)>);
+
+            impl<'kvs> Visitor<'kvs> for Group<'kvs> {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    match self.0.iter_mut().find(|group| group.0 == key) {
+                        Some(group) => group.1.push(value),
+                        None => self.0.push((key, vec![value])),
+                    }
+
+                    Ok(())
+                }
+            }
+
+            let mut group = Group(Vec::new());
+            self.0.visit(&mut group)?;
+
+            for (key, mut values) in group.0 {
+                let value = if values.len() == 1 {
+                    values.remove(0)
+                } else {
+                    Value::from_seq(values)
+                };
+
+                visitor.visit_pair(key, value)?;
+            }
+
+            Ok(())
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            get_default(self, key)
+        }
+
+        fn count(&self) -> usize {
+            count_default(self)
+        }
+    }
+
+    /// The result of calling `source::ordered_by`.
+    pub struct OrderedBy<S, F> {
+        source: S,
+        cmp: F,
+    }
+
+    /// Visit the pairs in this source in the order given by a comparison on their keys.
+    ///
+    /// This is useful for schemas that need a fixed order of fields. Pairs with
+    /// keys that compare equal keep the order they're visited in by the source.
+    /// Use [`by_priority`](fn.by_priority.html) to build a comparison from a
+    /// list of keys that should come first.
+    ///
+    /// The whole source is buffered while it's visited.
+    pub fn ordered_by<S, F>(source: S, cmp: F) -> OrderedBy<S, F>
+    where
+        S: Source,
+        F: Fn(&Key, &Key) -> Ordering,
+    {
+        OrderedBy { source, cmp }
+    }
+
+    impl<S, F> Source for OrderedBy<S, F>
+    where
+        S: Source,
+        F: Fn(&Key, &Key) -> Ordering,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            let mut pairs = buffer_pairs(&self.source)?;
+
+            pairs.sort_by(|a, b| (self.cmp)(&a.0, &b.0));
+
+            for (key, value) in pairs {
+                visitor.visit_pair(key, value)?;
+            }
+
+            Ok(())
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            self.source.get(key)
+        }
+
+        fn count(&self) -> usize {
+            self.source.count()
+        }
+    }
+
+    impl<S, F> fmt::Debug for OrderedBy<S, F>
+    where
+        S: fmt::Debug,
+    {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("OrderedBy")
+                .field("source", &self.source)
+                .finish()
+        }
+    }
+
+    /// Compare keys by their position in `keys`, for use with [`ordered_by`](fn.ordered_by.html).
+    ///
+    /// Keys in the list come first, in the order they're listed. Any other keys
+    /// come after them, sorted alphabetically.
+    pub fn by_priority(keys: &'static [&'static str]) -> impl Fn(&Key, &Key) -> Ordering {
+        move |a, b| {
+            let rank = |key: &Key| {
+                keys.iter()
+                    .position(|priority| *priority == key.as_str())
+                    .unwrap_or(keys.len())
+            };
+
+            rank(a)
+                .cmp(&rank(b))
+                .then_with(|| a.as_str().cmp(b.as_str()))
+        }
+    }
+
+    /// A visitor that escapes values before forwarding them to another visitor.
+    ///
+    /// Each value is rendered as a string and passed to the escape function. If it
+    /// returns the string unchanged, the original value is forwarded as-is. Otherwise,
+    /// the escaped string is forwarded in its place. Keys aren't escaped. Sinks can
+    /// use [`escape_syslog_sd`](fn.escape_syslog_sd.html) and
+    /// [`escape_gelf`](fn.escape_gelf.html), or their own escape function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use log::kv::source::{self, EscapingVisitor};
+    /// # use log::kv::{Error, Key, Source, Value, Visitor};
+    /// # struct Sink;
+    /// # impl<'kvs> Visitor<'kvs> for Sink {
+    /// #     fn visit_pair(&mut self, _: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> { Ok(()) }
+    /// # }
+    ///
+    /// let mut sink = Sink;
+    /// let mut visitor = EscapingVisitor::new(&mut sink, source::escape_syslog_sd);
+    ///
+    /// ("tag", "[prod]").visit(&mut visitor).unwrap();
+    /// ```
+    pub struct EscapingVisitor<'a, 'kvs: 'a, F> {
+        visitor: &'a mut dyn Visitor<'kvs>,
+        escape: F,
+    }
+
+    impl<'a, 'kvs, F> EscapingVisitor<'a, 'kvs, F>
+    where
+        F: Fn(&str) -> Cow<str>,
+    {
+        /// Wrap a visitor, escaping each value with `escape`.
+        pub fn new(visitor: &'a mut dyn Visitor<'kvs>, escape: F) -> Self {
+            EscapingVisitor { visitor, escape }
+        }
+    }
+
+    impl<'a, 'kvs, F> Visitor<'kvs> for EscapingVisitor<'a, 'kvs, F>
+    where
+        F: Fn(&str) -> Cow<str>,
+    {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            let rendered = value.to_string();
+
+            let value = match (self.escape)(&rendered) {
+                Cow::Borrowed(_) => value,
+                Cow::Owned(escaped) => Value::from_string(escaped),
+            };
+
+            self.visitor.visit_pair(key, value)
+        }
+    }
+
+    impl<'a, 'kvs, F> fmt::Debug for EscapingVisitor<'a, 'kvs, F> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("EscapingVisitor").finish()
+        }
+    }
+
+    /// Escape a value for a syslog structured data parameter.
+    ///
+    /// This follows the rules for a `PARAM-VALUE` in RFC 5424, where `"`, `\`,
+    /// and `]` are escaped with a `\`.
+    pub fn escape_syslog_sd(value: &str) -> Cow<str> {
+        escape_with(value, |c, escaped| match c {
+            '"' | '\\' | ']' => {
+                escaped.push('\\');
+                escaped.push(c);
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// Escape a value for a GELF field.
+    ///
+    /// GELF messages are JSON, so this escapes the value as the contents of a
+    /// JSON string, without the surrounding quotes. That means `"` and `\` are
+    /// escaped with a `\`, and control characters are escaped as `\n`, `\u0000`,
+    /// and so on.
+    pub fn escape_gelf(value: &str) -> Cow<str> {
+        escape_with(value, |c, escaped| {
+            match c {
+                '"' => escaped.push_str("\\\""),
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+                _ => return false,
+            }
+
+            true
+        })
+    }
+
+    // Only allocates if some character in the value is escaped
+    fn escape_with<F>(value: &str, escape: F) -> Cow<str>
+    where
+        F: Fn(char, &mut String) -> bool,
+    {
+        let mut escaped = String::new();
+
+        for (i, c) in value.char_indices() {
+            if escaped.is_empty() {
+                let mut buf = String::new();
+                if escape(c, &mut buf) {
+                    escaped.reserve(value.len() + buf.len());
+                    escaped.push_str(&value[..i]);
+                    escaped.push_str(&buf);
+                }
+            } else if !escape(c, &mut escaped) {
+                escaped.push(c);
+            }
+        }
+
+        if escaped.is_empty() {
+            Cow::Borrowed(value)
+        } else {
+            Cow::Owned(escaped)
+        }
+    }
+
+    /// Check that a source's `visit`, `get`, and `count` agree with each other.
+    ///
+    /// This is a test helper for implementors of [`Source`](trait.Source.html),
+    /// especially ones that override `get` or `count`. It checks that `count`
+    /// returns the number of pairs that are visited, and that `get` returns the
+    /// value each key was first visited with. Keys visited more than once should
+    /// resolve to their first value, the same as the default `get`, so a source
+    /// that returns a later duplicate fails. Values are compared by their `Debug`
+    /// output.
+    ///
+    /// Requires the `test-util` feature.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the source is inconsistent, or fails to visit.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn check_source_consistency<S>(source: &S)
+    where
+        S: Source + ?Sized,
+    {
+        struct Collect<'kvs>(Vec<(Key<'kvs>, String)>);
+
+        impl<'kvs> Visitor<'kvs> for Collect<'kvs> {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.push((key, format!("{:?}", value)));
+                Ok(())
+            }
+        }
+
+        let mut collect = Collect(Vec::new());
+        source
+            .visit(&mut collect)
+            .expect("failed to visit the source");
+
+        assert_eq!(
+            collect.0.len(),
+            source.count(),
+            "`count` doesn't match the number of visited pairs"
+        );
+
+        let mut visited = BTreeMap::new();
+        for (key, value) in collect.0 {
+            visited
+                .entry(key.as_str().to_owned())
+                .or_insert((key, value));
+        }
+
+        for (name, (key, value)) in visited {
+            let got = source
+                .get(key)
+                .unwrap_or_else(|| panic!("`get` didn't find the visited key `{}`", name));
+
+            assert_eq!(
+                value,
+                format!("{:?}", got),
+                "`get` doesn't match the first visited value for `{}`",
+                name
+            );
+        }
+    }
+
+    /// The result of calling `source::cap_keys`.
+    #[derive(Debug)]
+    pub struct CapKeys<S> {
+        source: S,
+        max: usize,
+        overflow_key: &'static str,
+    }
+
+    /// Limit the number of distinct keys in this source.
+    ///
+    /// This protects backends that are sensitive to the cardinality of keys, like
+    /// metrics sinks, from an explosion of fields. Pairs with the first `max`
+    /// distinct keys in the order they're visited are kept. Any other pairs are
+    /// dropped, and the number dropped is visited at the end as a single pair under
+    /// `overflow_key`. If nothing is dropped then there's no `overflow_key` pair.
+    pub fn cap_keys<S>(source: S, max: usize, overflow_key: &'static str) -> CapKeys<S>
+    where
+        S: Source,
+    {
+        CapKeys {
+            source,
+            max,
+            overflow_key,
+        }
+    }
+
+    impl<S> Source for CapKeys<S>
+    where
+        S: Source,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            struct CapVisitor<'a, 'kvs: 'a> {
+                max: usize,
+                seen: Vec<Key<'kvs>>,
+                overflow: u64,
+                visitor: &'a mut dyn Visitor<'kvs>,
+            }
+
+            impl<'a, 'kvs> Visitor<'kvs> for CapVisitor<'a, 'kvs> {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    if !self.seen.contains(&key) {
+                        if self.seen.len() >= self.max {
+                            self.overflow += 1;
+                            return Ok(());
+                        }
+
+                        self.seen.push(key.clone());
+                    }
+
+                    self.visitor.visit_pair(key, value)
+                }
+            }
+
+            let mut cap = CapVisitor {
+                max: self.max,
+                seen: Vec::new(),
+                overflow: 0,
+                visitor,
+            };
+            self.source.visit(&mut cap)?;
+
+            if cap.overflow > 0 {
+                cap.visitor
+                    .visit_pair(Key::from_str(self.overflow_key), Value::from(cap.overflow))?;
+            }
+
+            Ok(())
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            get_default(self, key)
+        }
+
+        fn count(&self) -> usize {
+            count_default(self)
+        }
+    }
+
+    /// The result of calling `source::stringify_values`.
+    #[derive(Debug)]
+    pub struct StringifyValues<S>(S);
+
+    /// Render every value in this source into a string.
+    ///
+    /// Each value is formatted using its `Display` implementation and visited
+    /// as an owned string, so sinks that only accept text see a uniform schema.
+    /// A number `1` is visited as the string `"1"`.
+    pub fn stringify_values<S>(source: S) -> StringifyValues<S>
+    where
+        S: Source,
+    {
+        StringifyValues(source)
+    }
+
+    impl<S> Source for StringifyValues<S>
+    where
+        S: Source,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            struct StringifyVisitor<'a, 'kvs: 'a>(&'a mut dyn Visitor<'kvs>);
+
+            impl<'a, 'kvs> Visitor<'kvs> for StringifyVisitor<'a, 'kvs> {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    self.0
+                        .visit_pair(key, Value::from_string(value.to_string()))
+                }
+            }
+
+            self.0.visit(&mut StringifyVisitor(visitor))
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            self.0
+                .get(key)
+                .map(|value| Value::from_string(value.to_string()))
+        }
+
+        fn count(&self) -> usize {
+            self.0.count()
+        }
+    }
+
+    /// The result of calling `source::parse_numeric_keys`.
+    #[derive(Debug)]
+    pub struct ParseNumericKeys<S> {
+        source: S,
+        keys: &'static [&'static str],
+    }
+
+    /// Parse string values that look like numbers, but only for the given keys.
+    ///
+    /// Values for keys in `keys` that are strings holding an integer or a finite
+    /// float are visited as numbers. All other values are visited unchanged, so
+    /// strings like zip codes that only look like numbers keep their formatting
+    /// unless their key is listed.
+    pub fn parse_numeric_keys<S>(source: S, keys: &'static [&'static str]) -> ParseNumericKeys<S>
+    where
+        S: Source,
+    {
+        ParseNumericKeys { source, keys }
+    }
+
+    fn parse_numeric(value: Value) -> Value {
+        let parsed = value.to_borrowed_str().and_then(|value| {
+            if let Ok(value) = value.parse::<i64>() {
+                Some(Value::from(value))
+            } else if let Ok(value) = value.parse::<u64>() {
+                Some(Value::from(value))
+            } else {
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .map(Value::from)
+            }
+        });
+
+        parsed.unwrap_or(value)
+    }
+
+    impl<S> ParseNumericKeys<S> {
+        fn parse<'v>(&self, key: &Key, value: Value<'v>) -> Value<'v> {
+            if self.keys.contains(&key.as_str()) {
+                parse_numeric(value)
+            } else {
+                value
+            }
+        }
+    }
+
+    impl<S> Source for ParseNumericKeys<S>
+    where
+        S: Source,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            struct ParseVisitor<'a, 'kvs: 'a, S: 'kvs> {
+                parse: &'kvs ParseNumericKeys<S>,
+                visitor: &'a mut dyn Visitor<'kvs>,
+            }
+
+            impl<'a, 'kvs, S> Visitor<'kvs> for ParseVisitor<'a, 'kvs, S> {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    let value = self.parse.parse(&key, value);
+                    self.visitor.visit_pair(key, value)
+                }
+            }
+
+            self.source.visit(&mut ParseVisitor {
+                parse: self,
+                visitor,
+            })
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            let value = self.source.get(key.clone())?;
+            Some(self.parse(&key, value))
+        }
+
+        fn count(&self) -> usize {
+            self.source.count()
+        }
+    }
+
+    /// The result of calling `source::catch_errors`.
+    #[derive(Debug)]
+    pub struct CaughtErrors<S> {
+        source: S,
+        errors: Mutex<Vec<Error>>,
+    }
+
+    /// Keep visiting the pairs in this source when a visitor fails on one of them.
+    ///
+    /// Errors returned by the visitor are collected instead of stopping the visit,
+    /// so a serializer can still produce the rest of its output. The collected
+    /// errors can be retrieved with [`take_errors`](struct.CaughtErrors.html#method.take_errors).
+    /// Visiting only fails if every pair failed, or if the source itself fails.
+    pub fn catch_errors<S>(source: S) -> CaughtErrors<S>
+    where
+        S: Source,
+    {
+        CaughtErrors {
+            source,
+            errors: Mutex::new(Vec::new()),
+        }
+    }
+
+    impl<S> CaughtErrors<S> {
+        /// Take the errors collected while visiting the source.
+        ///
+        /// Errors are collected in the order they happened, across every visit
+        /// since the last call to `take_errors`.
+        pub fn take_errors(&self) -> Vec<Error> {
+            mem::take(&mut *self.errors.lock().unwrap_or_else(|err| err.into_inner()))
+        }
+    }
+
+    impl<S> Source for CaughtErrors<S>
+    where
+        S: Source,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            struct CatchVisitor<'a, 'kvs: 'a> {
+                visitor: &'a mut dyn Visitor<'kvs>,
+                errors: Vec<Error>,
+                visited: usize,
+            }
+
+            impl<'a, 'kvs> Visitor<'kvs> for CatchVisitor<'a, 'kvs> {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    if let Err(err) = self.visitor.visit_pair(key, value) {
+                        self.errors.push(err);
+                    } else {
+                        self.visited += 1;
+                    }
+
+                    Ok(())
+                }
+            }
+
+            let mut catch = CatchVisitor {
+                visitor,
+                errors: Vec::new(),
+                visited: 0,
+            };
+            self.source.visit(&mut catch)?;
+
+            let failed = !catch.errors.is_empty() && catch.visited == 0;
+            self.errors
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .extend(catch.errors);
+
+            if failed {
+                Err(Error::msg("every pair failed to visit"))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            self.source.get(key)
+        }
+
+        fn count(&self) -> usize {
+            self.source.count()
+        }
+    }
+
+    /// The result of calling `source::from_env_prefix`.
+    #[derive(Clone, Debug)]
+    pub struct EnvSource {
+        prefix: &'static str,
+        strip_prefix: bool,
+    }
+
+    /// A source of the environment variables whose names start with `prefix`.
+    ///
+    /// The environment is read each time the source is visited, so it reflects
+    /// any variables set or removed since it was created. Variables whose names
+    /// or values aren't valid unicode are skipped.
+    pub fn from_env_prefix(prefix: &'static str) -> EnvSource {
+        EnvSource {
+            prefix,
+            strip_prefix: false,
+        }
+    }
+
+    impl EnvSource {
+        /// Remove the prefix from the names of variables when they're used as keys.
+        ///
+        /// So a variable `APP_PORT` with the prefix `APP_` is visited with the key `PORT`.
+        pub fn strip_prefix(mut self) -> Self {
+            self.strip_prefix = true;
+            self
+        }
+    }
+
+    impl Source for EnvSource {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            for (key, value) in env::vars_os() {
+                let (key, value) = match (key.into_string(), value.into_string()) {
+                    (Ok(key), Ok(value)) => (key, value),
+                    _ => continue,
+                };
+
+                if !key.starts_with(self.prefix) {
+                    continue;
+                }
+
+                let key = if self.strip_prefix {
+                    key[self.prefix.len()..].to_owned()
+                } else {
+                    key
+                };
+
+                visitor.visit_pair(Key::from_owned(key), Value::from_string(value))?;
+            }
+
+            Ok(())
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            let var = if self.strip_prefix {
+                env::var(format!("{}{}", self.prefix, key))
+            } else if key.as_str().starts_with(self.prefix) {
+                env::var(key.as_str())
+            } else {
+                return None;
+            };
+
+            var.ok().map(Value::from_string)
+        }
+
+        fn count(&self) -> usize {
+            count_default(self)
+        }
+    }
+
+    /// A source with details about the current process and the host it's running on.
+    ///
+    /// This source contains a `pid` key with the id of the current process, and
+    /// a `host` key with the name of the host, if one could be found. Chain it onto
+    /// records for centralized log collection.
+    #[derive(Clone, Debug)]
+    pub struct HostInfo {
+        pid: u32,
+        host: Option<String>,
+    }
+
+    impl HostInfo {
+        /// Capture details about the current process and host.
+        ///
+        /// Finding the hostname is best-effort and doesn't call into the platform.
+        /// It's read from the `HOSTNAME` or `COMPUTERNAME` environment variables,
+        /// falling back to the contents of `/etc/hostname`. If none of these are
+        /// available then the `host` key is omitted.
+        pub fn new() -> Self {
+            let host = env::var("HOSTNAME")
+                .or_else(|_| env::var("COMPUTERNAME"))
+                .ok()
+                .or_else(|| fs::read_to_string("/etc/hostname").ok())
+                .map(|host| host.trim().to_owned())
+                .filter(|host| !host.is_empty());
+
+            HostInfo {
+                pid: process::id(),
+                host,
+            }
+        }
+    }
+
+    impl Default for HostInfo {
+        fn default() -> Self {
+            HostInfo::new()
+        }
+    }
+
+    impl Source for HostInfo {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            visitor.visit_pair(Key::from_str("pid"), Value::from(self.pid))?;
+
+            if let Some(ref host) = self.host {
+                visitor.visit_pair(Key::from_str("host"), Value::from(&**host))?;
+            }
+
+            Ok(())
+        }
+
+        fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+            match key.as_str() {
+                "pid" => Some(Value::from(self.pid)),
+                "host" => self.host.as_ref().map(|host| Value::from(&**host)),
+                _ => None,
+            }
+        }
+
+        fn count(&self) -> usize {
+            match self.host {
+                Some(_) => 2,
+                None => 1,
+            }
+        }
+    }
+
+    struct FlattenVisitor<'a, 'kvs: 'a> {
+        prefix: Option<&'a str>,
+        visitor: &'a mut dyn Visitor<'kvs>,
+    }
+
+    impl<'a, 'kvs> Visitor<'kvs> for FlattenVisitor<'a, 'kvs> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            let key = match self.prefix {
+                Some(prefix) => Key::from_owned(format!("{}.{}", prefix, key)),
+                None => key,
+            };
+
+            match value.to_source() {
+                Some(source) => source.visit(&mut FlattenVisitor {
+                    prefix: Some(key.as_str()),
+                    visitor: &mut *self.visitor,
+                }),
+                None => self.visitor.visit_pair(key, value),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use kv::value::tests::Token;
+        use std::collections::{BTreeMap, HashMap};
+
+        #[test]
+        fn count() {
+            assert_eq!(1, Source::count(&Box::new(("a", 1))));
+            assert_eq!(2, Source::count(&vec![("a", 1), ("b", 2)]));
+        }
+
+        #[test]
+        fn get() {
+            let source = vec![("a", 1), ("b", 2), ("a", 1)];
+            assert_eq!(
+                Token::I64(1),
+                Source::get(&source, Key::from_str("a")).unwrap().to_token()
+            );
+
+            let source = Box::new(Option::None::<(&str, i32)>);
+            assert!(Source::get(&source, Key::from_str("a")).is_none());
+        }
+
+        #[test]
+        fn hash_map() {
+            let mut map = HashMap::new();
+            map.insert("a", 1);
+            map.insert("b", 2);
+
+            assert_eq!(2, Source::count(&map));
+            assert_eq!(
+                Token::I64(1),
+                Source::get(&map, Key::from_str("a")).unwrap().to_token()
+            );
+        }
+
+        #[test]
+        fn btree_map() {
+            let mut map = BTreeMap::new();
+            map.insert("a", 1);
+            map.insert("b", 2);
+
+            assert_eq!(2, Source::count(&map));
+            assert_eq!(
+                Token::I64(1),
+                Source::get(&map, Key::from_str("a")).unwrap().to_token()
+            );
+        }
+
+        #[test]
+        fn visit_timed() {
+            let source = vec![("a", 1), ("b", 2), ("c", 3)];
+
+            struct Collect(Vec<String>);
+
+            impl<'kvs> Visitor<'kvs> for Collect {
+                fn visit_pair(&mut self, key: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+                    self.0.push(key.to_string());
+                    Ok(())
+                }
+            }
+
+            let mut visitor = Collect(Vec::new());
+            let (result, elapsed) = super::visit_timed(&source, &mut visitor);
+
+            assert!(result.is_ok());
+            assert!(elapsed >= Duration::from_secs(0));
+            assert_eq!(vec!["a", "b", "c"], visitor.0);
+        }
+
+        #[test]
+        fn display_keys() {
+            let mut map = HashMap::new();
+            map.insert(1u32, "a");
+            map.insert(2u32, "b");
+
+            let source = super::from_display_keys(&map);
+
+            assert_eq!(2, Source::count(&source));
+            assert_eq!(
+                Token::Str("b".into()),
+                Source::get(&source, Key::from_str("2")).unwrap().to_token()
+            );
+            assert!(Source::get(&source, Key::from_str("3")).is_none());
+        }
+
+        #[test]
+        fn group_by_key() {
+            let source = vec![("tag", "a"), ("id", "1"), ("tag", "b"), ("tag", "c")];
+
+            let grouped = super::group_by_key(&source);
+
+            assert_eq!(2, Source::count(&grouped));
+            assert_eq!(
+                r#"{"tag": ["a", "b", "c"], "id": "1"}"#,
+                format!("{:?}", as_map(&grouped))
+            );
+            assert_eq!(
+                Token::Str("1".into()),
+                Source::get(&grouped, Key::from_str("id"))
+                    .unwrap()
+                    .to_token()
+            );
+        }
+
+        #[test]
+        fn ordered_by() {
+            let source = vec![
+                ("message", "hello"),
+                ("level", "info"),
+                ("b", "2"),
+                ("timestamp", "0"),
+                ("a", "1"),
+            ];
+
+            let ordered = super::ordered_by(&source, by_priority(&["timestamp", "level"]));
+
+            assert_eq!(5, Source::count(&ordered));
+            assert_eq!(
+                r#"{"timestamp": "0", "level": "info", "a": "1", "b": "2", "message": "hello"}"#,
+                format!("{:?}", as_map(&ordered))
+            );
+        }
+
+        #[test]
+        fn escaping_visitor() {
+            let source = &[("tag", Value::from("[prod]")), ("id", Value::from(1))] as &[_];
+
+            let mut out = Vec::new();
+            source
+                .visit(&mut EscapingVisitor::new(
+                    &mut CollectVisitor::new(&mut out),
+                    super::escape_syslog_sd,
+                ))
+                .unwrap();
+
+            assert_eq!(
+                vec![
+                    ("tag".to_owned(), "[prod\\]".to_owned()),
+                    ("id".to_owned(), "1".to_owned())
+                ],
+                out
+            );
+        }
+
+        #[test]
+        fn escape_syslog_sd() {
+            assert_eq!("a\\]b", super::escape_syslog_sd("a]b"));
+            assert_eq!("\\\"a\\\\\\\"", super::escape_syslog_sd("\"a\\\""));
+            assert!(match super::escape_syslog_sd("[prod") {
+                Cow::Borrowed(_) => true,
+                Cow::Owned(_) => false,
+            });
+        }
+
+        #[test]
+        fn escape_gelf() {
+            assert_eq!("a\\\"b\\nc", super::escape_gelf("a\"b\nc"));
+            assert_eq!("\\u0001", super::escape_gelf("\u{1}"));
+            assert_eq!("a]b", super::escape_gelf("a]b"));
+        }
+
+        #[test]
+        fn check_source_consistency() {
+            super::check_source_consistency(&[("a", 1), ("b", 2), ("a", 3)] as &[_]);
+            super::check_source_consistency(&super::cap_keys(
+                vec![("a", 1), ("b", 2), ("c", 3)],
+                2,
+                "overflow",
+            ));
+        }
+
+        #[test]
+        #[should_panic(expected = "`count` doesn't match")]
+        fn check_source_consistency_count() {
+            struct Inconsistent;
+
+            impl Source for Inconsistent {
+                fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+                    visitor.visit_pair(Key::from_str("a"), Value::from(1))
+                }
+
+                fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+                    get_default(self, key)
+                }
+
+                fn count(&self) -> usize {
+                    2
+                }
+            }
+
+            super::check_source_consistency(&Inconsistent);
+        }
+
+        #[test]
+        #[should_panic(expected = "`get` doesn't match")]
+        fn check_source_consistency_get() {
+            struct Inconsistent;
+
+            impl Source for Inconsistent {
+                fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+                    visitor.visit_pair(Key::from_str("a"), Value::from(1))
+                }
+
+                fn get<'v>(&'v self, _: Key) -> Option<Value<'v>> {
+                    Some(Value::from(2))
+                }
+
+                fn count(&self) -> usize {
+                    1
+                }
+            }
+
+            super::check_source_consistency(&Inconsistent);
+        }
+
+        #[test]
+        #[should_panic(expected = "`get` doesn't match")]
+        fn check_source_consistency_get_duplicate() {
+            struct Inconsistent;
+
+            impl Source for Inconsistent {
+                fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+                    visitor.visit_pair(Key::from_str("a"), Value::from(1))?;
+                    visitor.visit_pair(Key::from_str("a"), Value::from(2))
+                }
+
+                fn get<'v>(&'v self, _: Key) -> Option<Value<'v>> {
+                    Some(Value::from(2))
+                }
+
+                fn count(&self) -> usize {
+                    2
+                }
+            }
+
+            super::check_source_consistency(&Inconsistent);
+        }
+
+        #[test]
+        fn cap_keys() {
+            let source = vec![("a", 1), ("b", 2), ("a", 3), ("c", 4), ("d", 5), ("e", 6)];
+
+            let capped = super::cap_keys(&source, 3, "overflow");
+
+            assert_eq!(5, Source::count(&capped));
+            assert_eq!(
+                r#"{"a": 1, "b": 2, "a": 3, "c": 4, "overflow": 2}"#,
+                format!("{:?}", as_map(&capped))
+            );
+
+            let uncapped = super::cap_keys(&source, 5, "overflow");
+            assert!(Source::get(&uncapped, Key::from_str("overflow")).is_none());
+        }
+
+        #[test]
+        fn collect_map() {
+            let nested: &[_] = &[("c", true)];
+            let source = &[
+                ("b", Value::from(1)),
+                ("a", Value::from("x")),
+                ("n", Value::from_source(&nested)),
+            ] as &[_];
+
+            let collected = super::collect_map(source);
+
+            assert_eq!(3, Source::count(&collected));
+            assert_eq!(
+                Token::I64(1),
+                Source::get(&collected, Key::from_str("b"))
+                    .unwrap()
+                    .to_token()
+            );
+            assert_eq!(
+                r#"{"c": true}"#,
+                Source::get(&collected, Key::from_str("n"))
+                    .unwrap()
+                    .to_string()
+            );
+            assert_eq!(
+                Some("x"),
+                Source::get(&collected, Key::from_str("a"))
+                    .unwrap()
+                    .to_borrowed_str()
+            );
+
+            let rendered = |source: &dyn Source| {
+                let mut rendered = Vec::new();
+                for key in &["a", "b"] {
+                    rendered.push(source.get(Key::from_str(key)).unwrap().to_string());
+                }
+                rendered
+            };
+            assert_eq!(rendered(&source), rendered(&collected));
+        }
+
+        #[test]
+        fn from_env_prefix() {
+            std::env::set_var("LOG_TEST_ENV_SOURCE_PORT", "8080");
+
+            let source = super::from_env_prefix("LOG_TEST_ENV_SOURCE_");
+            assert_eq!(1, Source::count(&source));
+            assert_eq!(
+                Some("8080"),
+                Source::get(&source, Key::from_str("LOG_TEST_ENV_SOURCE_PORT"))
+                    .unwrap()
+                    .to_borrowed_str()
+            );
+
+            let stripped = super::from_env_prefix("LOG_TEST_ENV_SOURCE_").strip_prefix();
+            assert_eq!(r#"{"PORT": "8080"}"#, format!("{:?}", as_map(&stripped)));
+            assert!(Source::get(&stripped, Key::from_str("PORT")).is_some());
+        }
+
+        #[test]
+        fn collect_strings_into() {
+            let mut out = vec![("existing".to_owned(), "pair".to_owned())];
+
+            super::collect_strings_into(&[("a", 1)] as &[_], &mut out);
+            super::collect_strings_into(("b", "two"), &mut out);
+
+            assert_eq!(
+                vec![
+                    ("existing".to_owned(), "pair".to_owned()),
+                    ("a".to_owned(), "1".to_owned()),
+                    ("b".to_owned(), "two".to_owned()),
+                ],
+                out
+            );
+        }
+
+        #[test]
+        fn debug_dump() {
+            let source: &[(&str, &str)] = &[("b", "two"), ("a", "one"), ("c", "three")];
+
+            assert_eq!(
+                "a = \"one\"\nb = \"two\"\nc = \"three\"\n",
+                super::debug_dump(source)
+            );
+            assert_eq!("", super::debug_dump(None::<(&str, i32)>));
+        }
+
+        #[test]
+        fn dyn_sources() {
+            let a: Box<dyn Source> = Box::new(("a", 1));
+            let b: Box<dyn Source> = Box::new(("b", 2));
+            let c: Box<dyn Source> = Box::new(vec![("c", 3), ("d", 4)]);
+
+            let boxed = vec![a, b, c];
+            assert_eq!(4, Source::count(&boxed));
+            assert_eq!(
+                Some(3),
+                Source::get(&boxed, Key::from_str("c")).and_then(|v| v.to_i32())
+            );
+
+            let borrowed: Vec<&dyn Source> = boxed.iter().map(|source| &**source).collect();
+            assert_eq!(
+                r#"{"a": 1, "b": 2, "c": 3, "d": 4}"#,
+                format!("{:?}", as_map(&borrowed[..]))
+            );
+        }
+
+        #[test]
+        fn fields_hash() {
+            let a: &[(&str, i32)] = &[("a", 1), ("b", 2), ("c", 3)];
+            let b: &[(&str, i32)] = &[("c", 3), ("a", 1), ("b", 2)];
+            let c: &[(&str, i32)] = &[("a", 1), ("b", 2), ("c", 4)];
+
+            assert_eq!(super::fields_hash(a), super::fields_hash(b));
+            assert_ne!(super::fields_hash(a), super::fields_hash(c));
+        }
+
+        #[test]
+        fn mutex_source() {
+            let context = Mutex::new(super::collect_map(&[("user", "alice")] as &[_]));
+            let source = MutexSource::new(&context);
+
+            assert_eq!(r#"{"user": "alice"}"#, format!("{:?}", as_map(&source)));
+
+            context
+                .lock()
+                .unwrap()
+                .insert("request".to_owned(), Value::from(42).to_owned_value());
+
+            assert_eq!(2, Source::count(&source));
+            assert_eq!(
+                r#"{"request": 42, "user": "alice"}"#,
+                format!("{:?}", as_map(&source))
+            );
+        }
+
+        #[test]
+        fn mutex_source_visits_a_snapshot() {
+            struct Insert<'a>(&'a Mutex<BTreeMap<String, OwnedValue>>, Vec<String>);
+
+            impl<'a, 'kvs> Visitor<'kvs> for Insert<'a> {
+                fn visit_pair(&mut self, key: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+                    self.0
+                        .lock()
+                        .unwrap()
+                        .insert(format!("{}.copy", key), Value::from(true).to_owned_value());
+                    self.1.push(key.to_string());
+                    Ok(())
+                }
+            }
+
+            let context = Mutex::new(super::collect_map(&[("request", 42), ("user", 7)] as &[_]));
+            let source = MutexSource::new(&context);
+
+            let mut insert = Insert(&context, Vec::new());
+            source.visit(&mut insert).unwrap();
+
+            assert_eq!(vec!["request", "user"], insert.1);
+            assert_eq!(4, Source::count(&source));
+        }
+
+        #[test]
+        fn numeric_fields() {
+            let source: &[(&str, Value)] = &[
+                ("count", Value::from(3u8)),
+                ("user", Value::from("alice")),
+                ("latency", Value::from(1.5f64)),
+                ("delta", Value::from(-2i64)),
+                ("enabled", Value::from(true)),
+                ("port", Value::from("8080")),
+            ];
+
+            assert_eq!(
+                vec![
+                    ("count".to_owned(), 3.0),
+                    ("latency".to_owned(), 1.5),
+                    ("delta".to_owned(), -2.0),
+                ],
+                super::numeric_fields(source)
+            );
+        }
+
+        #[test]
+        fn host_info() {
+            let host = HostInfo::new();
+
+            assert_eq!(
+                Some(u64::from(std::process::id())),
+                Source::get(&host, Key::from_str("pid")).unwrap().to_u64()
+            );
+            assert_eq!(count_default(&host), Source::count(&host));
+        }
+
+        #[test]
+        fn stringify_values() {
+            let source = vec![("a", Value::from(1)), ("b", Value::from(true))];
+
+            let stringified = super::stringify_values(&source);
+
+            struct Collect(Vec<Token>);
+
+            impl<'kvs> Visitor<'kvs> for Collect {
+                fn visit_pair(&mut self, _: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    self.0.push(value.to_token());
+                    Ok(())
+                }
+            }
+
+            let mut collect = Collect(Vec::new());
+            stringified.visit(&mut collect).unwrap();
+
+            assert_eq!(
+                vec![Token::Str("1".into()), Token::Str("true".into())],
+                collect.0
+            );
+            assert_eq!(
+                Some("true"),
+                Source::get(&stringified, Key::from_str("b"))
+                    .unwrap()
+                    .to_borrowed_str()
+            );
+        }
+
+        #[test]
+        fn catch_errors() {
+            let source = vec![("a", 1), ("b", 2), ("c", 3)];
+
+            let caught = super::catch_errors(&source);
+
+            struct FailOn(&'static str, Vec<String>);
+
+            impl<'kvs> Visitor<'kvs> for FailOn {
+                fn visit_pair(&mut self, key: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+                    if key.as_str() == self.0 {
+                        return Err(Error::msg("unsupported key"));
+                    }
+
+                    self.1.push(key.to_string());
+                    Ok(())
+                }
+            }
+
+            let mut visitor = FailOn("b", Vec::new());
+            caught.visit(&mut visitor).unwrap();
+
+            assert_eq!(vec!["a", "c"], visitor.1);
+
+            let errors = caught.take_errors();
+            assert_eq!(1, errors.len());
+            assert_eq!("unsupported key", errors[0].to_string());
+            assert!(caught.take_errors().is_empty());
+
+            let single = super::catch_errors(&[("b", 2)] as &[_]);
+            assert!(single.visit(&mut FailOn("b", Vec::new())).is_err());
+            assert_eq!(1, single.take_errors().len());
+        }
+
+        #[test]
+        fn parse_numeric_keys() {
+            let source = vec![("port", "8080"), ("zip", "00501"), ("ratio", "0.5")];
+
+            let parsed = super::parse_numeric_keys(&source, &["port", "ratio"]);
+
+            struct Collect(Vec<Token>);
+
+            impl<'kvs> Visitor<'kvs> for Collect {
+                fn visit_pair(&mut self, _: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    self.0.push(value.to_token());
+                    Ok(())
+                }
+            }
+
+            let mut collect = Collect(Vec::new());
+            parsed.visit(&mut collect).unwrap();
+
+            assert_eq!(
+                vec![
+                    Token::I64(8080),
+                    Token::Str("00501".into()),
+                    Token::F64(0.5)
+                ],
+                collect.0
+            );
+            assert_eq!(
+                Token::I64(8080),
+                Source::get(&parsed, Key::from_str("port"))
+                    .unwrap()
+                    .to_token()
+            );
+            assert_eq!(
+                Some("00501"),
+                Source::get(&parsed, Key::from_str("zip"))
+                    .unwrap()
+                    .to_borrowed_str()
+            );
+        }
+
+        #[test]
+        fn flatten() {
+            let nested = vec![("b", 2), ("c", 3)];
+            let source = vec![
+                ("a", Value::from(1)),
+                ("n", Value::from_source(&nested)),
+                ("d", Value::from(4)),
+            ];
+
+            let flattened = super::flatten(&source);
+
+            assert_eq!(4, Source::count(&flattened));
+            assert_eq!(
+                Token::I64(3),
+                Source::get(&flattened, Key::from_str("n.c"))
+                    .unwrap()
+                    .to_token()
+            );
+            assert_eq!(
+                r#"{"a": 1, "n.b": 2, "n.c": 3, "d": 4}"#,
+                format!("{:?}", as_map(&flattened))
+            );
+            assert_eq!(
+                r#"{"a": 1, "n": {"b": 2, "c": 3}, "d": 4}"#,
+                format!("{:?}", as_map(&source))
+            );
+        }
+    }
+}
+
+/// The result of calling `Source::as_map`.
+pub struct AsMap<S>(S);
+
+/// Visit this source as a map.
+///
+/// When the map is serialized, the source is only visited once, so each value
+/// is only produced once, including values from a [`Fill`](../value/trait.Fill.html).
+/// Its pairs are buffered so the length of the map is known up front. Without
+/// the `std` feature, the map is streamed with an unknown length instead.
+pub fn as_map<S>(source: S) -> AsMap<S>
+where
+    S: Source,
+{
+    AsMap(source)
+}
+
+impl<S> Source for AsMap<S>
+where
+    S: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        self.0.visit(visitor)
+    }
+
+    fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+        self.0.get(key)
+    }
+
+    fn count(&self) -> usize {
+        self.0.count()
+    }
+}
+
+impl<S> fmt::Debug for AsMap<S>
+where
+    S: Source,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut f = f.debug_map();
+        self.0.visit(&mut f).map_err(|_| fmt::Error)?;
+        f.finish()
     }
 }
 
@@ -421,6 +2569,9 @@ where
 pub struct AsList<S>(S);
 
 /// Visit this source as a list.
+///
+/// As with [`as_map`](fn.as_map.html), each value is only produced once when
+/// the list is serialized.
 pub fn as_list<S>(source: S) -> AsList<S>
 where
     S: Source,
@@ -456,13 +2607,212 @@ where
     }
 }
 
-#[cfg(feature = "kv_unstable_sval")]
-mod sval_support {
-    use super::*;
+/// The result of calling `Source::as_indexed_list`.
+pub struct AsIndexedList<S>(S);
+
+/// Visit this source as a list of `(index, key, value)` triples.
+///
+/// The index is the position of each pair in the order it was visited,
+/// starting from `0`.
+pub fn as_indexed_list<S>(source: S) -> AsIndexedList<S>
+where
+    S: Source,
+{
+    AsIndexedList(source)
+}
+
+impl<S> Source for AsIndexedList<S>
+where
+    S: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        self.0.visit(visitor)
+    }
+
+    fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+        self.0.get(key)
+    }
+
+    fn count(&self) -> usize {
+        self.0.count()
+    }
+}
+
+impl<S> fmt::Debug for AsIndexedList<S>
+where
+    S: Source,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct DebugVisitor<'a, 'b: 'a, 'c: 'b>(&'a mut fmt::DebugList<'b, 'c>, usize);
+
+        impl<'a, 'b: 'a, 'c: 'b, 'kvs> Visitor<'kvs> for DebugVisitor<'a, 'b, 'c> {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.entry(&(self.1, key, value));
+                self.1 += 1;
+                Ok(())
+            }
+        }
+
+        let mut f = f.debug_list();
+        self.0
+            .visit(&mut DebugVisitor(&mut f, 0))
+            .map_err(|_| fmt::Error)?;
+        f.finish()
+    }
+}
+
+/// The result of calling `Source::as_flat_seq`.
+pub struct AsFlatSeq<S>(S);
+
+/// Visit this source as a flat sequence of alternating keys and values.
+///
+/// Pairs `a = 1` and `b = 2` are visited as `[a, 1, b, 2]`. This can be more
+/// compact than a map for binary formats like MessagePack or CBOR.
+pub fn as_flat_seq<S>(source: S) -> AsFlatSeq<S>
+where
+    S: Source,
+{
+    AsFlatSeq(source)
+}
+
+impl<S> Source for AsFlatSeq<S>
+where
+    S: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        self.0.visit(visitor)
+    }
+
+    fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+        self.0.get(key)
+    }
+
+    fn count(&self) -> usize {
+        self.0.count()
+    }
+}
+
+impl<S> fmt::Debug for AsFlatSeq<S>
+where
+    S: Source,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct DebugVisitor<'a, 'b: 'a, 'c: 'b>(&'a mut fmt::DebugList<'b, 'c>);
+
+        impl<'a, 'b: 'a, 'c: 'b, 'kvs> Visitor<'kvs> for DebugVisitor<'a, 'b, 'c> {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.entry(&key).entry(&value);
+                Ok(())
+            }
+        }
+
+        let mut f = f.debug_list();
+        self.0
+            .visit(&mut DebugVisitor(&mut f))
+            .map_err(|_| fmt::Error)?;
+        f.finish()
+    }
+}
+
+#[cfg(feature = "kv_unstable_sval")]
+mod sval_support {
+    use super::*;
+
+    use self::sval::value;
+
+    /// Visit a source once to stream it.
+    ///
+    /// With the `std` feature, the pairs are buffered so their count is
+    /// known before streaming begins.
+    #[cfg(feature = "std")]
+    fn stream_once<S, F>(source: &S, f: F) -> value::Result
+    where
+        S: Source,
+        F: FnOnce(Option<usize>, &dyn Source) -> value::Result,
+    {
+        let pairs = buffer_pairs(source)
+            .map_err(|_| self::sval::Error::msg("failed to visit key-values"))?;
+
+        f(Some(pairs.len()), &pairs)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn stream_once<S, F>(source: &S, f: F) -> value::Result
+    where
+        S: Source,
+        F: FnOnce(Option<usize>, &dyn Source) -> value::Result,
+    {
+        f(None, source)
+    }
+
+    impl<S> value::Value for AsMap<S>
+    where
+        S: Source,
+    {
+        fn stream(&self, stream: &mut value::Stream) -> value::Result {
+            struct StreamVisitor<'a, 'b>(&'a mut value::Stream<'b>);
+
+            impl<'a, 'b, 'kvs> Visitor<'kvs> for StreamVisitor<'a, 'b> {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    self.0
+                        .map_key(key)
+                        .map_err(|_| Error::msg("failed to stream map key"))?;
+                    self.0
+                        .map_value(value)
+                        .map_err(|_| Error::msg("failed to stream map value"))?;
+                    Ok(())
+                }
+            }
+
+            stream_once(&self.0, |len, source| {
+                stream
+                    .map_begin(len)
+                    .map_err(|_| self::sval::Error::msg("failed to begin map"))?;
+
+                source
+                    .visit(&mut StreamVisitor(stream))
+                    .map_err(|_| self::sval::Error::msg("failed to visit key-values"))?;
+
+                stream
+                    .map_end()
+                    .map_err(|_| self::sval::Error::msg("failed to end map"))
+            })
+        }
+    }
+
+    impl<S> value::Value for AsList<S>
+    where
+        S: Source,
+    {
+        fn stream(&self, stream: &mut value::Stream) -> value::Result {
+            struct StreamVisitor<'a, 'b>(&'a mut value::Stream<'b>);
+
+            impl<'a, 'b, 'kvs> Visitor<'kvs> for StreamVisitor<'a, 'b> {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    self.0
+                        .seq_elem((key, value))
+                        .map_err(|_| Error::msg("failed to stream seq entry"))?;
+                    Ok(())
+                }
+            }
+
+            stream_once(&self.0, |len, source| {
+                stream
+                    .seq_begin(len)
+                    .map_err(|_| self::sval::Error::msg("failed to begin seq"))?;
 
-    use self::sval::value;
+                source
+                    .visit(&mut StreamVisitor(stream))
+                    .map_err(|_| self::sval::Error::msg("failed to visit key-values"))?;
 
-    impl<S> value::Value for AsMap<S>
+                stream
+                    .seq_end()
+                    .map_err(|_| self::sval::Error::msg("failed to end seq"))
+            })
+        }
+    }
+
+    impl<S> value::Value for AsFlatSeq<S>
     where
         S: Source,
     {
@@ -472,54 +2822,75 @@ mod sval_support {
             impl<'a, 'b, 'kvs> Visitor<'kvs> for StreamVisitor<'a, 'b> {
                 fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
                     self.0
-                        .map_key(key)
-                        .map_err(|_| Error::msg("failed to stream map key"))?;
-                    self.0
-                        .map_value(value)
-                        .map_err(|_| Error::msg("failed to stream map value"))?;
+                        .seq_elem(key)
+                        .and_then(|_| self.0.seq_elem(value))
+                        .map_err(|_| Error::msg("failed to stream seq entry"))?;
                     Ok(())
                 }
             }
 
-            stream
-                .map_begin(Some(self.count()))
-                .map_err(|_| self::sval::Error::msg("failed to begin map"))?;
+            stream_once(&self.0, |len, source| {
+                stream
+                    .seq_begin(len.map(|len| len * 2))
+                    .map_err(|_| self::sval::Error::msg("failed to begin seq"))?;
 
-            self.visit(&mut StreamVisitor(stream))
-                .map_err(|_| self::sval::Error::msg("failed to visit key-values"))?;
+                source
+                    .visit(&mut StreamVisitor(stream))
+                    .map_err(|_| self::sval::Error::msg("failed to visit key-values"))?;
 
-            stream
-                .map_end()
-                .map_err(|_| self::sval::Error::msg("failed to end map"))
+                stream
+                    .seq_end()
+                    .map_err(|_| self::sval::Error::msg("failed to end seq"))
+            })
         }
     }
 
-    impl<S> value::Value for AsList<S>
+    impl<S> value::Value for AsIndexedList<S>
     where
         S: Source,
     {
         fn stream(&self, stream: &mut value::Stream) -> value::Result {
-            struct StreamVisitor<'a, 'b>(&'a mut value::Stream<'b>);
+            // `sval` only streams pairs as tuples, so entries are streamed as
+            // sequences of their index, key, and value
+            struct Entry<'kvs>(u64, Key<'kvs>, Value<'kvs>);
+
+            impl<'kvs> value::Value for Entry<'kvs> {
+                fn stream(&self, stream: &mut value::Stream) -> value::Result {
+                    stream.seq_begin(Some(3))?;
+
+                    stream.seq_elem(self.0)?;
+                    stream.seq_elem(&self.1)?;
+                    stream.seq_elem(&self.2)?;
+
+                    stream.seq_end()
+                }
+            }
+
+            struct StreamVisitor<'a, 'b>(&'a mut value::Stream<'b>, u64);
 
             impl<'a, 'b, 'kvs> Visitor<'kvs> for StreamVisitor<'a, 'b> {
                 fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
                     self.0
-                        .seq_elem((key, value))
+                        .seq_elem(Entry(self.1, key, value))
                         .map_err(|_| Error::msg("failed to stream seq entry"))?;
+                    self.1 += 1;
                     Ok(())
                 }
             }
 
-            stream
-                .seq_begin(Some(self.count()))
-                .map_err(|_| self::sval::Error::msg("failed to begin seq"))?;
+            stream_once(&self.0, |len, source| {
+                stream
+                    .seq_begin(len)
+                    .map_err(|_| self::sval::Error::msg("failed to begin seq"))?;
 
-            self.visit(&mut StreamVisitor(stream))
-                .map_err(|_| self::sval::Error::msg("failed to visit key-values"))?;
+                source
+                    .visit(&mut StreamVisitor(stream, 0))
+                    .map_err(|_| self::sval::Error::msg("failed to visit key-values"))?;
 
-            stream
-                .seq_end()
-                .map_err(|_| self::sval::Error::msg("failed to end seq"))
+                stream
+                    .seq_end()
+                    .map_err(|_| self::sval::Error::msg("failed to end seq"))
+            })
         }
     }
 
@@ -544,6 +2915,18 @@ mod sval_support {
                 msg: &'a str,
                 kvs: source::AsList<&'a dyn Source>,
             }
+
+            #[derive(Value)]
+            pub struct MyRecordAsFlatSeq<'a> {
+                msg: &'a str,
+                kvs: source::AsFlatSeq<&'a dyn Source>,
+            }
+
+            #[derive(Value)]
+            pub struct MyRecordAsIndexedList<'a> {
+                msg: &'a str,
+                kvs: source::AsIndexedList<&'a dyn Source>,
+            }
         }
     }
 }
@@ -572,83 +2955,530 @@ pub mod as_list {
 
     use super::*;
 
-    use self::serde::{Serialize, Serializer};
+    use self::serde::{Serialize, Serializer};
+
+    /// Serialize a `Source` as a list.
+    pub fn serialize<T, S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Source,
+        S: Serializer,
+    {
+        as_list(source).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "kv_unstable_serde")]
+pub mod as_flat_seq {
+    //! `serde` adapters for serializing a `Source` as a flat sequence of alternating keys and values.
+
+    use super::*;
+
+    use self::serde::{Serialize, Serializer};
+
+    /// Serialize a `Source` as a flat sequence of alternating keys and values.
+    pub fn serialize<T, S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Source,
+        S: Serializer,
+    {
+        as_flat_seq(source).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "kv_unstable_serde")]
+pub mod as_indexed_list {
+    //! `serde` adapters for serializing a `Source` as a list of `(index, key, value)` triples.
+
+    use super::*;
+
+    use self::serde::{Serialize, Serializer};
+
+    /// Serialize a `Source` as a list of `(index, key, value)` triples.
+    pub fn serialize<T, S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Source,
+        S: Serializer,
+    {
+        as_indexed_list(source).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "kv_unstable_serde")]
+mod serde_support {
+    use super::*;
+
+    use self::serde::ser::{
+        Error as SerError, Impossible, Serialize, SerializeMap, SerializeSeq, SerializeStruct,
+        Serializer,
+    };
+
+    impl<T> Serialize for AsMap<T>
+    where
+        T: Source,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let pairs = buffer_pairs(&self.0)
+                .map_err(|_| S::Error::custom("failed to visit key-values"))?;
+
+            let mut map = serializer.serialize_map(Some(pairs.len()))?;
+
+            for (key, value) in pairs {
+                map.serialize_entry(&key, &value)?;
+            }
+
+            map.end()
+        }
+    }
+
+    impl<T> Serialize for AsList<T>
+    where
+        T: Source,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let pairs =
+                buffer_pairs(&self.0).map_err(|_| S::Error::custom("failed to visit seq"))?;
+
+            let mut seq = serializer.serialize_seq(Some(pairs.len()))?;
+
+            for pair in pairs {
+                seq.serialize_element(&pair)?;
+            }
+
+            seq.end()
+        }
+    }
+
+    impl<T> Serialize for AsFlatSeq<T>
+    where
+        T: Source,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let pairs =
+                buffer_pairs(&self.0).map_err(|_| S::Error::custom("failed to visit seq"))?;
+
+            let mut seq = serializer.serialize_seq(Some(pairs.len() * 2))?;
+
+            for (key, value) in pairs {
+                seq.serialize_element(&key)?;
+                seq.serialize_element(&value)?;
+            }
+
+            seq.end()
+        }
+    }
+
+    impl<T> Serialize for AsIndexedList<T>
+    where
+        T: Source,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let pairs =
+                buffer_pairs(&self.0).map_err(|_| S::Error::custom("failed to visit seq"))?;
+
+            let mut seq = serializer.serialize_seq(Some(pairs.len()))?;
+
+            for (index, (key, value)) in pairs.into_iter().enumerate() {
+                seq.serialize_element(&(index, key, value))?;
+            }
+
+            seq.end()
+        }
+    }
+
+    /// The result of calling `source::from_serialize_flat`.
+    pub struct SerializeFlat<'v, T: ?Sized + 'v> {
+        fields: Option<Vec<(Key<'static>, Field<'v, T>)>>,
+    }
+
+    /// Flatten the top-level fields of a `serde::Serialize` value into key-value pairs.
+    ///
+    /// This is useful for capturing a `#[derive(Serialize)]` context struct as
+    /// a set of fields on a record, rather than a single nested value. Each field
+    /// of a struct, or entry of a map, becomes a pair. Its value is serialized
+    /// from the original when the pair is serialized, so nested values keep their
+    /// structure. Map keys are converted to strings.
+    ///
+    /// Only structs and maps can be flattened. Visiting the source of any other
+    /// value returns an error.
+    ///
+    /// The value is serialized once to find its keys when this function is called,
+    /// and again for each field when its value is serialized, so its `Serialize`
+    /// implementation should produce the same fields every time.
+    pub fn from_serialize_flat<'v, T>(value: &'v T) -> SerializeFlat<'v, T>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut keys = CollectKeys(Vec::new());
+
+        let fields = value.serialize(Fields(&mut keys)).ok().map(|()| {
+            keys.0
+                .into_iter()
+                .enumerate()
+                .map(|(index, key)| (key, Field { value, index }))
+                .collect()
+        });
+
+        SerializeFlat { fields }
+    }
+
+    impl<'v, T> Source for SerializeFlat<'v, T>
+    where
+        T: Serialize + ?Sized,
+    {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            let fields = self.fields.as_ref().ok_or_else(|| {
+                Error::msg("only structs and maps can be flattened into key-value pairs")
+            })?;
+
+            for (key, field) in fields {
+                visitor.visit_pair(key.clone(), Value::from_serde(field))?;
+            }
+
+            Ok(())
+        }
+
+        fn get<'a>(&'a self, key: Key) -> Option<Value<'a>> {
+            self.fields
+                .as_ref()?
+                .iter()
+                .find(|(field_key, _)| *field_key == key)
+                .map(|(_, field)| Value::from_serde(field))
+        }
+
+        fn count(&self) -> usize {
+            self.fields.as_ref().map(Vec::len).unwrap_or(0)
+        }
+    }
+
+    impl<'v, T> fmt::Debug for SerializeFlat<'v, T>
+    where
+        T: Serialize + ?Sized,
+    {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            fmt::Debug::fmt(&as_map(self), f)
+        }
+    }
+
+    // A single field of a flattened value, serialized by serializing the whole
+    // value and forwarding just the field at `index`.
+    struct Field<'v, T: ?Sized + 'v> {
+        value: &'v T,
+        index: usize,
+    }
+
+    impl<'v, T> Serialize for Field<'v, T>
+    where
+        T: Serialize + ?Sized,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut extract = ExtractField {
+                index: self.index,
+                serializer: Some(serializer),
+                result: None,
+            };
+
+            let _ = self.value.serialize(Fields(&mut extract));
+
+            extract
+                .result
+                .unwrap_or_else(|| Err(S::Error::custom("failed to serialize a flattened field")))
+        }
+    }
+
+    trait FieldSink {
+        fn field<T>(&mut self, index: usize, key: Key<'static>, value: &T) -> Result<(), FlatError>
+        where
+            T: Serialize + ?Sized;
+    }
+
+    struct CollectKeys(Vec<Key<'static>>);
+
+    impl FieldSink for CollectKeys {
+        fn field<T>(&mut self, _: usize, key: Key<'static>, _: &T) -> Result<(), FlatError>
+        where
+            T: Serialize + ?Sized,
+        {
+            self.0.push(key);
+            Ok(())
+        }
+    }
+
+    struct ExtractField<S: Serializer> {
+        index: usize,
+        serializer: Option<S>,
+        result: Option<Result<S::Ok, S::Error>>,
+    }
+
+    impl<S> FieldSink for ExtractField<S>
+    where
+        S: Serializer,
+    {
+        fn field<T>(&mut self, index: usize, _: Key<'static>, value: &T) -> Result<(), FlatError>
+        where
+            T: Serialize + ?Sized,
+        {
+            if index == self.index {
+                if let Some(serializer) = self.serializer.take() {
+                    self.result = Some(value.serialize(serializer));
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct FlatError(String);
+
+    impl fmt::Display for FlatError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl std::error::Error for FlatError {}
+
+    impl SerError for FlatError {
+        fn custom<T>(msg: T) -> Self
+        where
+            T: fmt::Display,
+        {
+            FlatError(msg.to_string())
+        }
+    }
+
+    fn unsupported() -> FlatError {
+        FlatError::custom("only structs and maps can be flattened into key-value pairs")
+    }
+
+    // A serializer for the top-level value being flattened.
+    struct Fields<'a, F: 'a>(&'a mut F);
+
+    // Each field or map entry is given to the sink along with its position.
+    struct FieldsCompound<'a, F: 'a> {
+        sink: &'a mut F,
+        index: usize,
+        key: Option<String>,
+    }
+
+    macro_rules! unsupported {
+        ($($method:ident($($arg:ty),*),)*) => {
+            $(
+                fn $method(self, $(_: $arg),*) -> Result<Self::Ok, Self::Error> {
+                    Err(unsupported())
+                }
+            )*
+        };
+    }
+
+    impl<'a, F> Serializer for Fields<'a, F>
+    where
+        F: FieldSink,
+    {
+        type Ok = ();
+        type Error = FlatError;
+
+        type SerializeSeq = Impossible<(), FlatError>;
+        type SerializeTuple = Impossible<(), FlatError>;
+        type SerializeTupleStruct = Impossible<(), FlatError>;
+        type SerializeTupleVariant = Impossible<(), FlatError>;
+        type SerializeMap = FieldsCompound<'a, F>;
+        type SerializeStruct = FieldsCompound<'a, F>;
+        type SerializeStructVariant = Impossible<(), FlatError>;
+
+        unsupported! {
+            serialize_bool(bool),
+            serialize_i8(i8),
+            serialize_i16(i16),
+            serialize_i32(i32),
+            serialize_i64(i64),
+            serialize_u8(u8),
+            serialize_u16(u16),
+            serialize_u32(u32),
+            serialize_u64(u64),
+            serialize_f32(f32),
+            serialize_f64(f64),
+            serialize_char(char),
+            serialize_str(&str),
+            serialize_bytes(&[u8]),
+            serialize_none(),
+            serialize_unit(),
+            serialize_unit_struct(&'static str),
+            serialize_unit_variant(&'static str, u32, &'static str),
+        }
+
+        fn serialize_some<T>(self, _: &T) -> Result<Self::Ok, Self::Error>
+        where
+            T: Serialize + ?Sized,
+        {
+            Err(unsupported())
+        }
+
+        fn serialize_newtype_struct<T>(
+            self,
+            _: &'static str,
+            value: &T,
+        ) -> Result<Self::Ok, Self::Error>
+        where
+            T: Serialize + ?Sized,
+        {
+            value.serialize(self)
+        }
+
+        fn serialize_newtype_variant<T>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &T,
+        ) -> Result<Self::Ok, Self::Error>
+        where
+            T: Serialize + ?Sized,
+        {
+            Err(unsupported())
+        }
+
+        fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+            Err(unsupported())
+        }
+
+        fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+            Err(unsupported())
+        }
+
+        fn serialize_tuple_struct(
+            self,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+            Err(unsupported())
+        }
+
+        fn serialize_tuple_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+            Err(unsupported())
+        }
 
-    /// Serialize a `Source` as a list.
-    pub fn serialize<T, S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        T: Source,
-        S: Serializer,
-    {
-        as_list(source).serialize(serializer)
-    }
-}
+        fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+            Ok(FieldsCompound {
+                sink: self.0,
+                index: 0,
+                key: None,
+            })
+        }
 
-#[cfg(feature = "kv_unstable_serde")]
-mod serde_support {
-    use super::*;
+        fn serialize_struct(
+            self,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeStruct, Self::Error> {
+            Ok(FieldsCompound {
+                sink: self.0,
+                index: 0,
+                key: None,
+            })
+        }
 
-    use self::serde::ser::{Error as SerError, Serialize, SerializeMap, SerializeSeq, Serializer};
+        fn serialize_struct_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeStructVariant, Self::Error> {
+            Err(unsupported())
+        }
+    }
 
-    impl<T> Serialize for AsMap<T>
+    impl<'a, F> FieldsCompound<'a, F>
     where
-        T: Source,
+        F: FieldSink,
     {
-        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        fn field<T>(&mut self, key: Key<'static>, value: &T) -> Result<(), FlatError>
         where
-            S: Serializer,
+            T: Serialize + ?Sized,
         {
-            struct SerializerVisitor<'a, S>(&'a mut S);
+            let index = self.index;
+            self.index += 1;
 
-            impl<'a, 'kvs, S> Visitor<'kvs> for SerializerVisitor<'a, S>
-            where
-                S: SerializeMap,
-            {
-                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
-                    self.0
-                        .serialize_entry(&key, &value)
-                        .map_err(|_| Error::msg("failed to serialize map entry"))?;
-                    Ok(())
-                }
-            }
+            self.sink.field(index, key, value)
+        }
+    }
 
-            let mut map = serializer.serialize_map(Some(self.count()))?;
+    impl<'a, F> SerializeStruct for FieldsCompound<'a, F>
+    where
+        F: FieldSink,
+    {
+        type Ok = ();
+        type Error = FlatError;
 
-            self.visit(&mut SerializerVisitor(&mut map))
-                .map_err(|_| S::Error::custom("failed to visit key-values"))?;
+        fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+        where
+            T: Serialize + ?Sized,
+        {
+            self.field(Key::from_str(key), value)
+        }
 
-            map.end()
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            Ok(())
         }
     }
 
-    impl<T> Serialize for AsList<T>
+    impl<'a, F> SerializeMap for FieldsCompound<'a, F>
     where
-        T: Source,
+        F: FieldSink,
     {
-        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        type Ok = ();
+        type Error = FlatError;
+
+        fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
         where
-            S: Serializer,
+            T: Serialize + ?Sized,
         {
-            struct SerializerVisitor<'a, S>(&'a mut S);
+            let key = Value::from_serde(&key);
 
-            impl<'a, 'kvs, S> Visitor<'kvs> for SerializerVisitor<'a, S>
-            where
-                S: SerializeSeq,
-            {
-                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
-                    self.0
-                        .serialize_element(&(key, value))
-                        .map_err(|_| Error::msg("failed to serialize seq entry"))?;
-                    Ok(())
-                }
-            }
+            // String keys are used as-is, and other keys are formatted
+            self.key = Some(match key.to_str() {
+                Some(key) => key.into_owned(),
+                None => key.to_string(),
+            });
+            Ok(())
+        }
 
-            let mut seq = serializer.serialize_seq(Some(self.count()))?;
+        fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+        where
+            T: Serialize + ?Sized,
+        {
+            let key = self
+                .key
+                .take()
+                .ok_or_else(|| FlatError::custom("a map value was serialized without a key"))?;
 
-            self.visit(&mut SerializerVisitor(&mut seq))
-                .map_err(|_| S::Error::custom("failed to visit seq"))?;
+            self.field(Key::from_owned(key), value)
+        }
 
-            seq.end()
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            Ok(())
         }
     }
 
@@ -656,6 +3486,9 @@ mod serde_support {
     mod tests {
         use super::*;
 
+        extern crate serde_test;
+        use self::serde_test::{assert_ser_tokens, Token};
+
         use self::serde::Serialize;
 
         use crate::kv::source;
@@ -678,6 +3511,250 @@ mod serde_support {
                 kvs: &'a dyn Source,
             }
         }
+
+        #[test]
+        fn as_map_announces_len() {
+            let source = &[Some(("a", 1)), None, Some(("b", 2))] as &[_];
+
+            assert_ser_tokens(
+                &source::as_map(source),
+                &[
+                    Token::Map { len: Some(2) },
+                    Token::Str("a"),
+                    Token::I64(1),
+                    Token::Str("b"),
+                    Token::I64(2),
+                    Token::MapEnd,
+                ],
+            );
+        }
+
+        #[test]
+        fn as_flat_seq() {
+            let source = &[("a", 1), ("b", 2)] as &[_];
+
+            assert_ser_tokens(
+                &source::as_flat_seq(source),
+                &[
+                    Token::Seq { len: Some(4) },
+                    Token::Str("a"),
+                    Token::I64(1),
+                    Token::Str("b"),
+                    Token::I64(2),
+                    Token::SeqEnd,
+                ],
+            );
+        }
+
+        #[test]
+        fn as_indexed_list() {
+            let source = &[("a", "x"), ("b", "y"), ("c", "z")] as &[_];
+
+            let mut tokens = vec![Token::Seq { len: Some(3) }];
+            for &(index, key, value) in &[(0, "a", "x"), (1, "b", "y"), (2, "c", "z")] {
+                tokens.extend_from_slice(&[
+                    Token::Tuple { len: 3 },
+                    Token::U64(index),
+                    Token::Str(key),
+                    Token::Str(value),
+                    Token::TupleEnd,
+                ]);
+            }
+            tokens.push(Token::SeqEnd);
+
+            assert_ser_tokens(&source::as_indexed_list(source), &tokens);
+        }
+
+        #[test]
+        fn serialize_produces_values_once() {
+            use kv::value::{Fill, Slot};
+            use std::cell::Cell;
+
+            struct Counter<'a>(&'a Cell<usize>);
+
+            impl<'a> Fill for Counter<'a> {
+                fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+                    self.0.set(self.0.get() + 1);
+                    slot.fill_any(self.0.get())
+                }
+            }
+
+            let calls = Cell::new(0);
+            let counter = Counter(&calls);
+            let source = &[
+                ("a", Value::from_fill(&counter)),
+                ("b", Value::from_fill(&counter)),
+            ] as &[_];
+
+            assert_ser_tokens(
+                &source::as_map(source),
+                &[
+                    Token::Map { len: Some(2) },
+                    Token::Str("a"),
+                    Token::U64(1),
+                    Token::Str("b"),
+                    Token::U64(2),
+                    Token::MapEnd,
+                ],
+            );
+            assert_eq!(2, calls.get());
+
+            calls.set(0);
+            assert_ser_tokens(
+                &source::as_list(source),
+                &[
+                    Token::Seq { len: Some(2) },
+                    Token::Tuple { len: 2 },
+                    Token::Str("a"),
+                    Token::U64(1),
+                    Token::TupleEnd,
+                    Token::Tuple { len: 2 },
+                    Token::Str("b"),
+                    Token::U64(2),
+                    Token::TupleEnd,
+                    Token::SeqEnd,
+                ],
+            );
+            assert_eq!(2, calls.get());
+        }
+
+        #[test]
+        fn serialize_visits_source_once() {
+            use std::cell::Cell;
+
+            struct Counted<'a>(&'a Cell<usize>);
+
+            impl<'a> Source for Counted<'a> {
+                fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+                    self.0.set(self.0.get() + 1);
+                    visitor.visit_pair(Key::from_str("a"), Value::from(1))?;
+                    visitor.visit_pair(Key::from_str("b"), Value::from(2))
+                }
+
+                fn get<'v>(&'v self, key: Key) -> Option<Value<'v>> {
+                    get_default(self, key)
+                }
+
+                fn count(&self) -> usize {
+                    count_default(self)
+                }
+            }
+
+            let visits = Cell::new(0);
+            let source = Counted(&visits);
+
+            assert_ser_tokens(
+                &source::as_map(&source),
+                &[
+                    Token::Map { len: Some(2) },
+                    Token::Str("a"),
+                    Token::I64(1),
+                    Token::Str("b"),
+                    Token::I64(2),
+                    Token::MapEnd,
+                ],
+            );
+            assert_eq!(1, visits.get());
+
+            visits.set(0);
+            assert_ser_tokens(
+                &source::as_list(&source),
+                &[
+                    Token::Seq { len: Some(2) },
+                    Token::Tuple { len: 2 },
+                    Token::Str("a"),
+                    Token::I64(1),
+                    Token::TupleEnd,
+                    Token::Tuple { len: 2 },
+                    Token::Str("b"),
+                    Token::I64(2),
+                    Token::TupleEnd,
+                    Token::SeqEnd,
+                ],
+            );
+            assert_eq!(1, visits.get());
+
+            visits.set(0);
+            assert_ser_tokens(
+                &source::as_flat_seq(&source),
+                &[
+                    Token::Seq { len: Some(4) },
+                    Token::Str("a"),
+                    Token::I64(1),
+                    Token::Str("b"),
+                    Token::I64(2),
+                    Token::SeqEnd,
+                ],
+            );
+            assert_eq!(1, visits.get());
+
+            visits.set(0);
+            assert_ser_tokens(
+                &source::as_indexed_list(&source),
+                &[
+                    Token::Seq { len: Some(2) },
+                    Token::Tuple { len: 3 },
+                    Token::U64(0),
+                    Token::Str("a"),
+                    Token::I64(1),
+                    Token::TupleEnd,
+                    Token::Tuple { len: 3 },
+                    Token::U64(1),
+                    Token::Str("b"),
+                    Token::I64(2),
+                    Token::TupleEnd,
+                    Token::SeqEnd,
+                ],
+            );
+            assert_eq!(1, visits.get());
+        }
+
+        #[test]
+        fn from_serialize_flat() {
+            #[derive(Serialize)]
+            struct Context {
+                user: &'static str,
+                attempts: u32,
+            }
+
+            let context = Context {
+                user: "alice",
+                attempts: 3,
+            };
+            let flat = source::from_serialize_flat(&context);
+
+            assert_eq!(2, flat.count());
+            assert_ser_tokens(
+                &source::as_map(&flat),
+                &[
+                    Token::Map { len: Some(2) },
+                    Token::Str("user"),
+                    Token::Str("alice"),
+                    Token::Str("attempts"),
+                    Token::U32(3),
+                    Token::MapEnd,
+                ],
+            );
+            assert_eq!(
+                Some(3),
+                flat.get(Key::from_str("attempts")).and_then(|v| v.to_u64())
+            );
+
+            let mut map = std::collections::BTreeMap::new();
+            map.insert("region", "eu");
+
+            let flat = source::from_serialize_flat(&map);
+            assert_eq!(
+                Some("eu".into()),
+                flat.get(Key::from_str("region"))
+                    .and_then(|v| v.to_str().map(|v| v.into_owned()))
+            );
+
+            let mut out = Vec::new();
+            assert!(source::from_serialize_flat(&42)
+                .visit(&mut CollectVisitor::new(&mut out))
+                .is_err());
+        }
     }
 }
 
@@ -720,6 +3797,10 @@ mod tests {
         assert_eq!(1, Source::count(&("a", 1)));
         assert_eq!(2, Source::count(&[("a", 1), ("b", 2)] as &[_]));
         assert_eq!(0, Source::count(&Option::None::<(&str, i32)>));
+        assert_eq!(
+            1,
+            Source::count(&[Some(("a", 1)), None] as &[Option<(&str, i32)>])
+        );
         assert_eq!(1, Source::count(&OnePair { key: "a", value: 1 }));
     }
 
@@ -740,6 +3821,306 @@ mod tests {
         assert!(Source::get(&source, Key::from_str("a")).is_none());
     }
 
+    #[test]
+    fn to_value_slice() {
+        let source = &[("a", &1 as &dyn ToValue), ("b", &"two" as &dyn ToValue)] as &[_];
+
+        assert_eq!(2, Source::count(source));
+        assert_eq!(
+            Token::I64(1),
+            Source::get(source, Key::from_str("a")).unwrap().to_token()
+        );
+        assert_eq!(
+            Token::Str("two".into()),
+            Source::get(source, Key::from_str("b")).unwrap().to_token()
+        );
+    }
+
+    #[test]
+    fn tee() {
+        struct Count(usize);
+
+        impl<'kvs> Visitor<'kvs> for Count {
+            fn visit_pair(&mut self, _: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+                self.0 += 1;
+                Ok(())
+            }
+        }
+
+        struct Collect(Vec<String>);
+
+        impl<'kvs> Visitor<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.push(format!("{}={}", key, value));
+                Ok(())
+            }
+        }
+
+        let source = &[("a", 1), ("b", 2), ("c", 3)] as &[_];
+
+        let mut count = Count(0);
+        let mut collect = Collect(Vec::new());
+        source
+            .visit(&mut super::tee(&mut count, &mut collect))
+            .unwrap();
+
+        assert_eq!(3, count.0);
+        assert_eq!(vec!["a=1", "b=2", "c=3"], collect.0);
+    }
+
+    #[test]
+    fn validate_keys() {
+        let no_spaces = |key: &str| !key.contains(' ');
+
+        assert!(super::validate_keys(&[("a", 1), ("b", 2)] as &[_], no_spaces).is_ok());
+        assert!(super::validate_keys(&[("a", 1), ("b c", 2)] as &[_], no_spaces).is_err());
+    }
+
+    #[test]
+    fn validating_visitor() {
+        struct Collect(Vec<String>);
+
+        impl<'kvs> Visitor<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+                self.0.push(key.to_string());
+                Ok(())
+            }
+        }
+
+        let source = &[("a", 1), ("b c", 2), ("d", 3)] as &[_];
+
+        let mut collect = Collect(Vec::new());
+        let result = source.visit(&mut ValidatingVisitor::new(&mut collect, |key: &str| {
+            !key.contains(' ')
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(vec!["a"], collect.0);
+    }
+
+    #[test]
+    fn context_overlay() {
+        let base = &[("user", "base"), ("service", "api")] as &[_];
+        let extra = &[("user", "extra")] as &[_];
+
+        let overlay = ContextOverlay::new(&base, &extra);
+
+        assert_eq!(
+            Token::Str("extra".into()),
+            Source::get(&overlay, Key::from_str("user"))
+                .unwrap()
+                .to_token()
+        );
+        assert_eq!(
+            Token::Str("api".into()),
+            Source::get(&overlay, Key::from_str("service"))
+                .unwrap()
+                .to_token()
+        );
+        assert_eq!(3, Source::count(&overlay));
+        assert_eq!(
+            r#"{"user": "extra", "user": "base", "service": "api"}"#,
+            format!("{:?}", super::as_map(&overlay))
+        );
+    }
+
+    #[test]
+    fn estimated_text_size() {
+        let source = &[("a", Value::from(1)), ("bc", Value::from("a string"))] as &[_];
+
+        let mut text = Vec::new();
+        for &(key, ref value) in source {
+            text.push(format!("{}={}", key, value));
+        }
+        let text = text.join(" ");
+
+        assert_eq!("a=1 bc=a string", text);
+        assert_eq!(text.len(), super::estimated_text_size(source));
+        assert_eq!(0, super::estimated_text_size(Option::None::<(&str, i32)>));
+    }
+
+    #[test]
+    fn lookup() {
+        use std::cell::Cell;
+
+        let lookups = Cell::new(0);
+        let source = super::lookup(|key| {
+            lookups.set(lookups.get() + 1);
+
+            match key {
+                "a" => Some(Value::from(1)),
+                _ => None,
+            }
+        });
+
+        assert_eq!(
+            Token::I64(1),
+            Source::get(&source, Key::from_str("a")).unwrap().to_token()
+        );
+        assert!(Source::get(&source, Key::from_str("b")).is_none());
+        assert_eq!(2, lookups.get());
+
+        assert_eq!(0, Source::count(&source));
+        assert_eq!("{}", format!("{:?}", super::as_map(&source)));
+    }
+
+    #[test]
+    fn get_ignore_case() {
+        let source = &[("User_Id", 1), ("b", 2)] as &[_];
+
+        assert_eq!(
+            Token::I64(1),
+            super::get_ignore_case(source, "user_id")
+                .unwrap()
+                .to_token()
+        );
+        assert!(Source::get(source, Key::from_str("user_id")).is_none());
+        assert!(super::get_ignore_case(source, "c").is_none());
+    }
+
+    #[test]
+    fn get_as() {
+        let source: &[(&str, Value)] = &[("count", Value::from(3)), ("name", Value::from("a"))];
+
+        assert_eq!(Some(3i64), super::get_as(source, "count"));
+        assert_eq!(None::<i64>, super::get_as(source, "name"));
+        assert_eq!(None::<i64>, super::get_as(source, "missing"));
+    }
+
+    #[test]
+    fn first() {
+        let source = &[("a", 1), ("b", 2)] as &[_];
+
+        let (key, value) = super::first(source).unwrap();
+        assert_eq!("a", key.as_str());
+        assert_eq!(Token::I64(1), value.to_token());
+
+        assert!(super::first(&None::<(&str, i32)>).is_none());
+    }
+
+    #[test]
+    fn find_map() {
+        let source = &[("a", 1), ("b", 5), ("c", 10)] as &[_];
+
+        let mut visited = 0;
+        let found = super::find_map(source, |key, value| {
+            visited += 1;
+
+            match value.to_i64() {
+                Some(value) if value > 3 => Some(key.as_str().to_owned()),
+                _ => None,
+            }
+        });
+
+        assert_eq!(Some("b".to_owned()), found);
+        assert_eq!(2, visited);
+
+        assert_eq!(
+            None,
+            super::find_map(source, |_, value| value.to_i64().filter(|v| *v > 10))
+        );
+    }
+
+    #[test]
+    fn enumerated() {
+        struct Collect(Vec<Option<usize>>);
+
+        impl<'kvs> Visitor<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+                self.0.push(key.index());
+                Ok(())
+            }
+        }
+
+        let source = &[("a", 1), ("b", 2), ("c", 3)] as &[_];
+
+        let mut collect = Collect(Vec::new());
+        source.visit(&mut collect).unwrap();
+        assert_eq!(vec![None, None, None], collect.0);
+
+        let mut collect = Collect(Vec::new());
+        super::enumerated(source).visit(&mut collect).unwrap();
+        assert_eq!(vec![Some(0), Some(1), Some(2)], collect.0);
+    }
+
+    #[test]
+    fn with_default() {
+        struct Collect(Vec<String>);
+
+        impl<'kvs> Visitor<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.push(format!("{}={}", key, value));
+                Ok(())
+            }
+        }
+
+        let missing = super::with_default(&[("a", "1")] as &[_], "region", "unknown");
+
+        let mut collect = Collect(Vec::new());
+        missing.visit(&mut collect).unwrap();
+        assert_eq!(vec!["a=1", "region=unknown"], collect.0);
+        assert_eq!(2, missing.count());
+        assert_eq!(
+            "unknown",
+            missing.get(Key::from_str("region")).unwrap().to_string()
+        );
+
+        let present =
+            super::with_default(&[("a", "1"), ("region", "eu")] as &[_], "region", "unknown");
+
+        let mut collect = Collect(Vec::new());
+        present.visit(&mut collect).unwrap();
+        assert_eq!(vec!["a=1", "region=eu"], collect.0);
+        assert_eq!(2, present.count());
+        assert_eq!(
+            "eu",
+            present.get(Key::from_str("region")).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn retain_keys() {
+        struct Collect(Vec<String>);
+
+        impl<'kvs> Visitor<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.push(format!("{}={}", key, value));
+                Ok(())
+            }
+        }
+
+        let source = &[("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)] as &[_];
+        let retained = super::retain_keys(source, &["b", "d"]);
+
+        let mut collect = Collect(Vec::new());
+        retained.visit(&mut collect).unwrap();
+
+        assert_eq!(vec!["b=2", "d=4"], collect.0);
+        assert_eq!(2, Source::count(&retained));
+        assert!(Source::get(&retained, Key::from_str("a")).is_none());
+        assert!(Source::get(&retained, Key::from_str("d")).is_some());
+    }
+
+    #[test]
+    fn as_flat_seq() {
+        let source = &[("a", 1), ("b", 2)] as &[_];
+
+        assert_eq!(
+            r#"["a", 1, "b", 2]"#,
+            format!("{:?}", super::as_flat_seq(source))
+        );
+    }
+
+    #[test]
+    fn as_indexed_list() {
+        let source = &[("a", 1), ("b", 2)] as &[_];
+
+        assert_eq!(
+            r#"[(0, "a", 1), (1, "b", 2)]"#,
+            format!("{:?}", super::as_indexed_list(source))
+        );
+    }
+
     #[test]
     fn as_map() {
         let _ = crate::kv::source::as_map(("a", 1));
@@ -1,6 +1,13 @@
 //! Structured values.
 
+use std::any::Any;
+use std::cmp;
 use std::fmt;
+use std::num::{Saturating, Wrapping};
+#[cfg(feature = "std")]
+use std::rc::Rc;
+use std::sync::atomic;
+use std::time::Duration;
 
 extern crate value_bag;
 
@@ -12,8 +19,15 @@ extern crate serde;
 
 use self::value_bag::ValueBag;
 
+use kv::source::{self, Source};
+#[cfg(feature = "std")]
+use kv::Key;
+
 pub use kv::Error;
 
+#[cfg(feature = "kv_unstable_std")]
+pub use self::std_support::{since, Since};
+
 /// A type that can be converted into a [`Value`](struct.Value.html).
 pub trait ToValue {
     /// Perform the conversion.
@@ -37,6 +51,100 @@ impl<'v> ToValue for Value<'v> {
     }
 }
 
+/// A type that can be extracted from a [`Value`](struct.Value.html).
+///
+/// This is implemented for primitive types using the conversions on `Value`,
+/// like [`Value::to_i64`](struct.Value.html#method.to_i64). With the
+/// `kv_unstable_std` feature, it's also implemented for `String`.
+pub trait FromValue: Sized {
+    /// Try convert a value into this type.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+/// A type that can produce a [`Value`](struct.Value.html) on demand.
+///
+/// Use [`Value::from_fill`](struct.Value.html#method.from_fill) to capture a `Fill`.
+/// Its value isn't produced until it's formatted, serialized, or converted, so values
+/// that are expensive to compute are skipped unless a backend actually needs them.
+pub trait Fill {
+    /// Fill a value into the given slot.
+    fn fill(&self, slot: &mut Slot) -> Result<(), Error>;
+}
+
+impl<'a, T> Fill for &'a T
+where
+    T: Fill + ?Sized,
+{
+    fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+        (**self).fill(slot)
+    }
+}
+
+/// A slot for a value produced by a [`Fill`](trait.Fill.html).
+pub struct Slot<'s> {
+    filled: bool,
+    fill: &'s mut dyn FnMut(Value) -> Result<(), Error>,
+}
+
+impl<'s> Slot<'s> {
+    fn new(fill: &'s mut dyn FnMut(Value) -> Result<(), Error>) -> Self {
+        Slot {
+            filled: false,
+            fill,
+        }
+    }
+
+    /// Fill the slot with a value.
+    ///
+    /// A slot can only be filled once. Attempting to fill it again will return an error.
+    pub fn fill(&mut self, value: Value) -> Result<(), Error> {
+        if self.filled {
+            return Err(Error::msg("the slot has already been filled"));
+        }
+
+        self.filled = true;
+        (self.fill)(value)
+    }
+
+    /// Fill the slot with a type implementing `ToValue`.
+    pub fn fill_any<T>(&mut self, value: T) -> Result<(), Error>
+    where
+        T: ToValue,
+    {
+        self.fill(value.to_value())
+    }
+}
+
+impl<'s> fmt::Debug for Slot<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Slot")
+            .field("filled", &self.filled)
+            .finish()
+    }
+}
+
+/// Produce the value of a `Fill` and pass it to `f`.
+///
+/// If the slot isn't filled then `f` is given an empty value.
+fn with_filled<R>(fill: &dyn Fill, f: impl FnOnce(Value) -> R) -> Result<R, Error> {
+    let mut f = Some(f);
+    let mut result = None;
+
+    fill.fill(&mut Slot::new(&mut |value| {
+        if let Some(f) = f.take() {
+            result = Some(f(value));
+        }
+
+        Ok(())
+    }))?;
+
+    if let Some(f) = f.take() {
+        result = Some(f(Value::from_value_bag(())));
+    }
+
+    Ok(result.expect("the fill function wasn't called"))
+}
+
 /// A value in a structured key-value pair.
 ///
 /// # Capturing values
@@ -106,8 +214,53 @@ impl<'v> ToValue for Value<'v> {
 ///
 /// assert_eq!(Some(42), value.to_i32());
 /// ```
+#[derive(Clone)]
 pub struct Value<'v> {
-    inner: ValueBag<'v>,
+    inner: Inner<'v>,
+}
+
+#[derive(Clone)]
+enum Inner<'v> {
+    ValueBag(ValueBag<'v>),
+    Source(&'v dyn Source),
+    Fill(FillRef<'v>),
+    Precision(ValueBag<'v>, usize),
+    Saturated(ValueBag<'v>),
+    Bits(u64, &'v [(u64, &'static str)]),
+    StrSlice(&'v [&'v str]),
+    Bytes(&'v [u8]),
+}
+
+#[derive(Clone)]
+enum FillRef<'v> {
+    Borrowed(&'v dyn Fill),
+    #[cfg(feature = "std")]
+    Shared(Rc<dyn Fill + 'v>),
+    #[cfg(feature = "std")]
+    Owned(Rc<OwnedValue>),
+}
+
+impl<'v> Fill for FillRef<'v> {
+    fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+        match *self {
+            FillRef::Borrowed(fill) => fill.fill(slot),
+            #[cfg(feature = "std")]
+            FillRef::Shared(ref fill) => fill.fill(slot),
+            #[cfg(feature = "std")]
+            FillRef::Owned(ref value) => value.fill(slot),
+        }
+    }
+}
+
+impl<'v> Inner<'v> {
+    fn value_bag(&self) -> Option<&ValueBag<'v>> {
+        match *self {
+            Inner::ValueBag(ref inner)
+            | Inner::Precision(ref inner, _)
+            | Inner::Saturated(ref inner) => Some(inner),
+            _ => None,
+        }
+    }
 }
 
 impl<'v> Value<'v> {
@@ -125,7 +278,7 @@ impl<'v> Value<'v> {
         T: fmt::Debug + 'static,
     {
         Value {
-            inner: ValueBag::capture_debug(value),
+            inner: Inner::ValueBag(ValueBag::capture_debug(value)),
         }
     }
 
@@ -135,7 +288,7 @@ impl<'v> Value<'v> {
         T: fmt::Display + 'static,
     {
         Value {
-            inner: ValueBag::capture_display(value),
+            inner: Inner::ValueBag(ValueBag::capture_display(value)),
         }
     }
 
@@ -146,7 +299,7 @@ impl<'v> Value<'v> {
         T: std::error::Error + 'static,
     {
         Value {
-            inner: ValueBag::capture_error(err),
+            inner: Inner::ValueBag(ValueBag::capture_error(err)),
         }
     }
 
@@ -157,7 +310,7 @@ impl<'v> Value<'v> {
         T: self::serde::Serialize + 'static,
     {
         Value {
-            inner: ValueBag::capture_serde1(value),
+            inner: Inner::ValueBag(ValueBag::capture_serde1(value)),
         }
     }
 
@@ -168,7 +321,7 @@ impl<'v> Value<'v> {
         T: self::sval::value::Value + 'static,
     {
         Value {
-            inner: ValueBag::capture_sval1(value),
+            inner: Inner::ValueBag(ValueBag::capture_sval1(value)),
         }
     }
 
@@ -178,7 +331,7 @@ impl<'v> Value<'v> {
         T: fmt::Debug,
     {
         Value {
-            inner: ValueBag::from_debug(value),
+            inner: Inner::ValueBag(ValueBag::from_debug(value)),
         }
     }
 
@@ -188,18 +341,21 @@ impl<'v> Value<'v> {
         T: fmt::Display,
     {
         Value {
-            inner: ValueBag::from_display(value),
+            inner: Inner::ValueBag(ValueBag::from_display(value)),
         }
     }
 
     /// Get a value from a type implementing `serde::Serialize`.
+    ///
+    /// The type doesn't need to implement `Debug` or `Display`. When the value
+    /// is formatted, it's rendered from its `Serialize` implementation instead.
     #[cfg(feature = "kv_unstable_serde")]
     pub fn from_serde<T>(value: &'v T) -> Self
     where
         T: self::serde::Serialize,
     {
         Value {
-            inner: ValueBag::from_serde1(value),
+            inner: Inner::ValueBag(ValueBag::from_serde1(value)),
         }
     }
 
@@ -210,21 +366,21 @@ impl<'v> Value<'v> {
         T: self::sval::value::Value,
     {
         Value {
-            inner: ValueBag::from_sval1(value),
+            inner: Inner::ValueBag(ValueBag::from_sval1(value)),
         }
     }
 
     /// Get a value from a dynamic `std::fmt::Debug`.
     pub fn from_dyn_debug(value: &'v dyn fmt::Debug) -> Self {
         Value {
-            inner: ValueBag::from_dyn_debug(value),
+            inner: Inner::ValueBag(ValueBag::from_dyn_debug(value)),
         }
     }
 
     /// Get a value from a dynamic `std::fmt::Display`.
     pub fn from_dyn_display(value: &'v dyn fmt::Display) -> Self {
         Value {
-            inner: ValueBag::from_dyn_display(value),
+            inner: Inner::ValueBag(ValueBag::from_dyn_display(value)),
         }
     }
 
@@ -232,7 +388,7 @@ impl<'v> Value<'v> {
     #[cfg(feature = "kv_unstable_std")]
     pub fn from_dyn_error(err: &'v (dyn std::error::Error + 'static)) -> Self {
         Value {
-            inner: ValueBag::from_dyn_error(err),
+            inner: Inner::ValueBag(ValueBag::from_dyn_error(err)),
         }
     }
 
@@ -240,7 +396,141 @@ impl<'v> Value<'v> {
     #[cfg(feature = "kv_unstable_sval")]
     pub fn from_dyn_sval(value: &'v dyn self::sval::value::Value) -> Self {
         Value {
-            inner: ValueBag::from_dyn_sval1(value),
+            inner: Inner::ValueBag(ValueBag::from_dyn_sval1(value)),
+        }
+    }
+
+    /// Get a value from a type-erased `Any`.
+    ///
+    /// If the value is a primitive, like an integer, `bool`, `char`, or string,
+    /// then it's captured as that primitive. Any other type is captured as the
+    /// string `"<unknown>"`.
+    pub fn from_any_dyn(value: &'v dyn Any) -> Self {
+        macro_rules! downcast {
+            ($($ty:ty,)*) => {
+                $(
+                    if let Some(value) = value.downcast_ref::<$ty>() {
+                        return Value::from(*value);
+                    }
+                )*
+            };
+        }
+
+        downcast![
+            usize,
+            u8,
+            u16,
+            u32,
+            u64,
+            isize,
+            i8,
+            i16,
+            i32,
+            i64,
+            f32,
+            f64,
+            char,
+            bool,
+            &'static str,
+        ];
+
+        #[cfg(feature = "std")]
+        if let Some(value) = value.downcast_ref::<String>() {
+            return Value::from(&**value);
+        }
+
+        Value::from("<unknown>")
+    }
+
+    /// Get a value from a nested source of key-value pairs.
+    ///
+    /// The value is formatted and serialized as a map.
+    /// Use [`source::flatten`](../source/fn.flatten.html) to expand its pairs.
+    pub fn from_source<S>(value: &'v S) -> Self
+    where
+        S: Source,
+    {
+        Value::from_dyn_source(value)
+    }
+
+    /// Get a value from a dynamic nested source of key-value pairs.
+    pub fn from_dyn_source(value: &'v dyn Source) -> Self {
+        Value {
+            inner: Inner::Source(value),
+        }
+    }
+
+    /// Try get the nested source of key-value pairs this value was created from.
+    pub fn to_source(&self) -> Option<&'v dyn Source> {
+        match self.inner {
+            Inner::Source(source) => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Get a value from a type implementing `Fill`.
+    ///
+    /// The value isn't produced until this value is formatted, serialized, or
+    /// converted into a primitive with a method like [`to_i64`](#method.to_i64).
+    /// The produced value isn't stored anywhere, so it can't be borrowed with
+    /// [`to_borrowed_str`](#method.to_borrowed_str) or downcast.
+    pub fn from_fill<T>(value: &'v T) -> Self
+    where
+        T: Fill,
+    {
+        Value::from_dyn_fill(value)
+    }
+
+    /// Get a value from a dynamic `Fill`.
+    pub fn from_dyn_fill(value: &'v dyn Fill) -> Self {
+        Value {
+            inner: Inner::Fill(FillRef::Borrowed(value)),
+        }
+    }
+
+    /// Get a value from a slice of strings.
+    ///
+    /// The value is serialized as a sequence of strings, and formatted as a
+    /// list, like `["a", "b"]`.
+    pub fn from_str_slice(values: &'v [&'v str]) -> Self {
+        Value {
+            inner: Inner::StrSlice(values),
+        }
+    }
+
+    /// Get a value from a slice of bytes.
+    ///
+    /// The value is serialized as bytes, and formatted as a list of numbers,
+    /// like `[104, 105]`.
+    pub fn from_bytes(value: &'v [u8]) -> Self {
+        Value {
+            inner: Inner::Bytes(value),
+        }
+    }
+
+    /// Get a value from a sequence of values.
+    ///
+    /// The values are filled as a single sequence when they're needed.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_seq(values: Vec<Value<'v>>) -> Self {
+        Value {
+            inner: Inner::Fill(FillRef::Shared(Rc::new(Seq(values)))),
+        }
+    }
+
+    /// Get a value from an owned string.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_string(value: String) -> Self {
+        Value::from_owned(OwnedValue {
+            inner: OwnedInner::Str(value),
+        })
+    }
+
+    /// Get a value from an owned value.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_owned(value: OwnedValue) -> Self {
+        Value {
+            inner: Inner::Fill(FillRef::Owned(Rc::new(value))),
         }
     }
 
@@ -250,30 +540,96 @@ impl<'v> Value<'v> {
         T: Into<ValueBag<'v>>,
     {
         Value {
-            inner: value.into(),
+            inner: Inner::ValueBag(value.into()),
         }
     }
 
     /// Check whether this value can be downcast to `T`.
     pub fn is<T: 'static>(&self) -> bool {
-        self.inner.is::<T>()
+        match self.inner.value_bag() {
+            Some(inner) => inner.is::<T>(),
+            None => false,
+        }
     }
 
     /// Try downcast this value to `T`.
     pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
-        self.inner.downcast_ref::<T>()
+        self.inner
+            .value_bag()
+            .and_then(|inner| inner.downcast_ref::<T>())
+    }
+
+    // Durations are captured as themselves so structured backends can downcast
+    // them, but are treated as a number of seconds everywhere else
+    fn duration_secs(&self) -> Option<f64> {
+        self.downcast_ref::<Duration>()
+            .map(|duration| duration.as_secs_f64())
     }
 }
 
 impl<'v> fmt::Debug for Value<'v> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(&self.inner, f)
+        if let Some(secs) = self.duration_secs() {
+            return fmt::Debug::fmt(&secs, f);
+        }
+
+        match self.inner {
+            Inner::ValueBag(ref inner)
+            | Inner::Precision(ref inner, _)
+            | Inner::Saturated(ref inner) => fmt::Debug::fmt(inner, f),
+            Inner::Source(source) => fmt::Debug::fmt(&source::as_map(source), f),
+            Inner::Fill(ref fill) => {
+                with_filled(fill, |value| fmt::Debug::fmt(&value, f)).unwrap_or(Err(fmt::Error))
+            }
+            Inner::Bits(value, names) => f.debug_list().entries(set_bits(value, names)).finish(),
+            Inner::StrSlice(values) => f.debug_list().entries(values).finish(),
+            Inner::Bytes(value) => fmt::Debug::fmt(value, f),
+        }
     }
 }
 
 impl<'v> fmt::Display for Value<'v> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Display::fmt(&self.inner, f)
+        if let Some(secs) = self.duration_secs() {
+            return fmt::Display::fmt(&secs, f);
+        }
+
+        match self.inner {
+            Inner::ValueBag(ref inner) => fmt::Display::fmt(inner, f),
+            Inner::Precision(ref inner, precision) => match inner.to_f64() {
+                Some(value) => write!(f, "{:.*}", precision, value),
+                None => fmt::Display::fmt(inner, f),
+            },
+            Inner::Saturated(ref inner) => write!(f, "{} (saturated)", inner),
+            Inner::Source(source) => fmt::Debug::fmt(&source::as_map(source), f),
+            Inner::Fill(ref fill) => {
+                with_filled(fill, |value| fmt::Display::fmt(&value, f)).unwrap_or(Err(fmt::Error))
+            }
+            Inner::Bits(value, names) => {
+                for (i, name) in set_bits(value, names).enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    f.write_str(name)?;
+                }
+
+                Ok(())
+            }
+            Inner::StrSlice(values) => f.debug_list().entries(values).finish(),
+            Inner::Bytes(value) => fmt::Debug::fmt(value, f),
+        }
+    }
+}
+
+impl ToValue for dyn Fill {
+    fn to_value(&self) -> Value {
+        Value::from_dyn_fill(self)
+    }
+}
+
+impl ToValue for dyn Source {
+    fn to_value(&self) -> Value {
+        Value::from_dyn_source(self)
     }
 }
 
@@ -302,14 +658,88 @@ impl<'v> self::serde::Serialize for Value<'v> {
     where
         S: self::serde::Serializer,
     {
-        self.inner.serialize(s)
+        // Durations are serialized as `{ secs, nanos }` so they can be reconstructed exactly
+        if let Some(duration) = self.downcast_ref::<Duration>() {
+            return self::serde::Serialize::serialize(duration, s);
+        }
+
+        match self.inner {
+            Inner::ValueBag(ref inner)
+            | Inner::Precision(ref inner, _)
+            | Inner::Saturated(ref inner) => inner.serialize(s),
+            Inner::Source(source) => self::serde::Serialize::serialize(&source::as_map(source), s),
+            Inner::Fill(ref fill) => {
+                with_filled(fill, |value| self::serde::Serialize::serialize(&value, s))
+                    .unwrap_or_else(|err| Err(self::serde::ser::Error::custom(err)))
+            }
+            Inner::Bits(value, names) => s.collect_seq(set_bits(value, names)),
+            Inner::StrSlice(values) => s.collect_seq(values),
+            Inner::Bytes(value) => s.serialize_bytes(value),
+        }
     }
 }
 
 #[cfg(feature = "kv_unstable_sval")]
 impl<'v> self::sval::value::Value for Value<'v> {
     fn stream(&self, stream: &mut self::sval::value::Stream) -> self::sval::value::Result {
-        self::sval::value::Value::stream(&self.inner, stream)
+        // Durations are streamed as `{ secs, nanos }` so they can be reconstructed exactly
+        if let Some(duration) = self.downcast_ref::<Duration>() {
+            stream.map_begin(Some(2))?;
+
+            stream.map_key("secs")?;
+            stream.map_value(duration.as_secs())?;
+
+            stream.map_key("nanos")?;
+            stream.map_value(duration.subsec_nanos())?;
+
+            return stream.map_end();
+        }
+
+        match self.inner {
+            Inner::ValueBag(ref inner)
+            | Inner::Precision(ref inner, _)
+            | Inner::Saturated(ref inner) => self::sval::value::Value::stream(inner, stream),
+            Inner::Source(source) => {
+                self::sval::value::Value::stream(&source::as_map(source), stream)
+            }
+            Inner::Fill(ref fill) => with_filled(fill, |value| stream.any(&value))
+                .unwrap_or_else(|_| Err(self::sval::Error::msg("failed to fill a value"))),
+            Inner::Bits(value, names) => {
+                stream.seq_begin(None)?;
+                for name in set_bits(value, names) {
+                    stream.seq_elem(name)?;
+                }
+                stream.seq_end()
+            }
+            Inner::StrSlice(values) => {
+                stream.seq_begin(Some(values.len()))?;
+                for value in values {
+                    stream.seq_elem(value)?;
+                }
+                stream.seq_end()
+            }
+            Inner::Bytes(value) => {
+                stream.seq_begin(Some(value.len()))?;
+                for byte in value {
+                    stream.seq_elem(byte)?;
+                }
+                stream.seq_end()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "kv_unstable_sval")]
+impl<'v> Value<'v> {
+    /// Stream this value directly into an `sval` stream.
+    ///
+    /// This is the same as streaming the value through its `sval::value::Value`
+    /// implementation, without needing to wrap the stream first.
+    pub fn stream<S>(&self, stream: &mut S) -> Result<(), self::sval::Error>
+    where
+        S: self::sval::stream::Stream,
+    {
+        self::sval::stream(stream, self).map(|_| ())
     }
 }
 
@@ -320,120 +750,705 @@ impl ToValue for dyn self::sval::value::Value {
     }
 }
 
-impl ToValue for str {
-    fn to_value(&self) -> Value {
-        Value::from(self)
+/// A sequence of values produced by `Value::from_seq`.
+#[cfg(feature = "std")]
+struct Seq<'v>(Vec<Value<'v>>);
+
+#[cfg(feature = "std")]
+impl<'v> Fill for Seq<'v> {
+    fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+        #[cfg(feature = "kv_unstable_serde")]
+        {
+            slot.fill(Value::from_serde(self))
+        }
+        #[cfg(all(not(feature = "kv_unstable_serde"), feature = "kv_unstable_sval"))]
+        {
+            slot.fill(Value::from_sval(self))
+        }
+        #[cfg(not(any(feature = "kv_unstable_serde", feature = "kv_unstable_sval")))]
+        {
+            slot.fill(Value::from_debug(self))
+        }
     }
 }
 
-impl<'v> From<&'v str> for Value<'v> {
-    fn from(value: &'v str) -> Self {
-        Value::from_value_bag(value)
+#[cfg(feature = "std")]
+impl<'v> fmt::Debug for Seq<'v> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(&self.0).finish()
     }
 }
 
-impl ToValue for () {
-    fn to_value(&self) -> Value {
-        Value::from_value_bag(())
+#[cfg(feature = "kv_unstable_serde")]
+impl<'v> self::serde::Serialize for Seq<'v> {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: self::serde::Serializer,
+    {
+        s.collect_seq(&self.0)
     }
 }
 
-impl<T> ToValue for Option<T>
-where
-    T: ToValue,
-{
-    fn to_value(&self) -> Value {
-        match *self {
-            Some(ref value) => value.to_value(),
-            None => Value::from_value_bag(()),
+#[cfg(all(feature = "std", feature = "kv_unstable_sval"))]
+impl<'v> self::sval::value::Value for Seq<'v> {
+    fn stream(&self, stream: &mut self::sval::value::Stream) -> self::sval::value::Result {
+        stream.seq_begin(Some(self.0.len()))?;
+        for value in &self.0 {
+            stream.seq_elem(value)?;
         }
+        stream.seq_end()
     }
 }
 
-macro_rules! impl_to_value_primitive {
-    ($($into_ty:ty,)*) => {
-        $(
-            impl ToValue for $into_ty {
-                fn to_value(&self) -> Value {
-                    Value::from(*self)
-                }
-            }
+/// An owned value that doesn't borrow from where it was captured.
+///
+/// Booleans, characters, numbers, and strings keep their type, and nested
+/// sources keep their pairs. Any other value, including 128-bit integers, is
+/// kept as its `Display` form.
+/// Use [`Value::to_owned_value`](struct.Value.html#method.to_owned_value) to
+/// capture one, and `ToValue` to borrow it as a `Value` again.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq)]
+pub struct OwnedValue {
+    inner: OwnedInner,
+}
 
-            impl<'v> From<$into_ty> for Value<'v> {
-                fn from(value: $into_ty) -> Self {
-                    Value::from_value_bag(value)
-                }
-            }
-        )*
-    };
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq)]
+enum OwnedInner {
+    None,
+    Bool(bool),
+    Char(char),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+    Source(Vec<(String, OwnedValue)>),
 }
 
-macro_rules! impl_value_to_primitive {
-    ($(#[doc = $doc:tt] $into_name:ident -> $into_ty:ty,)*) => {
-        impl<'v> Value<'v> {
-            $(
-                #[doc = $doc]
-                pub fn $into_name(&self) -> Option<$into_ty> {
-                    self.inner.$into_name()
-                }
-            )*
+#[cfg(feature = "std")]
+impl<'v> Value<'v> {
+    /// Capture this value as an owned value.
+    pub fn to_owned_value(&self) -> OwnedValue {
+        if let Inner::Fill(ref fill) = self.inner {
+            return with_filled(fill, |value| value.to_owned_value()).unwrap_or(OwnedValue {
+                inner: OwnedInner::None,
+            });
+        }
+
+        if let Some(secs) = self.duration_secs() {
+            return OwnedValue {
+                inner: OwnedInner::F64(secs),
+            };
         }
+
+        let primitive = self.inner.value_bag().and_then(|inner| {
+            let mut visitor = OwnedPrimitive(None);
+            let _ = inner.visit(&mut visitor);
+            visitor.0
+        });
+
+        let inner = if let Some(primitive) = primitive {
+            primitive
+        } else if let Some(source) = self.to_source() {
+            struct Collect(Vec<(String, OwnedValue)>);
+
+            impl<'kvs> source::Visitor<'kvs> for Collect {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    self.0
+                        .push((key.as_str().to_owned(), value.to_owned_value()));
+                    Ok(())
+                }
+            }
+
+            let mut collect = Collect(Vec::new());
+            let _ = source.visit(&mut collect);
+
+            OwnedInner::Source(collect.0)
+        } else {
+            OwnedInner::Str(self.to_string())
+        };
+
+        OwnedValue { inner }
     }
 }
 
-impl_to_value_primitive![usize, u8, u16, u32, u64, isize, i8, i16, i32, i64, f32, f64, char, bool,];
+/// Capture the primitive in a value, if it has one.
+#[cfg(feature = "std")]
+struct OwnedPrimitive(Option<OwnedInner>);
 
-impl_value_to_primitive![
-    #[doc = "Try convert this value into a `usize`."]
-    to_usize -> usize,
-    #[doc = "Try convert this value into a `u8`."]
-    to_u8 -> u8,
-    #[doc = "Try convert this value into a `u16`."]
-    to_u16 -> u16,
-    #[doc = "Try convert this value into a `u32`."]
-    to_u32 -> u32,
-    #[doc = "Try convert this value into a `u64`."]
-    to_u64 -> u64,
-    #[doc = "Try convert this value into a `isize`."]
-    to_isize -> isize,
-    #[doc = "Try convert this value into a `i8`."]
-    to_i8 -> i8,
-    #[doc = "Try convert this value into a `i16`."]
-    to_i16 -> i16,
-    #[doc = "Try convert this value into a `i32`."]
-    to_i32 -> i32,
-    #[doc = "Try convert this value into a `i64`."]
-    to_i64 -> i64,
-    #[doc = "Try convert this value into a `f32`."]
-    to_f32 -> f32,
-    #[doc = "Try convert this value into a `f64`."]
-    to_f64 -> f64,
-    #[doc = "Try convert this value into a `char`."]
-    to_char -> char,
-    #[doc = "Try convert this value into a `bool`."]
-    to_bool -> bool,
-];
+#[cfg(feature = "std")]
+impl<'v> self::value_bag::visit::Visit<'v> for OwnedPrimitive {
+    fn visit_any(&mut self, _: ValueBag) -> Result<(), self::value_bag::Error> {
+        Ok(())
+    }
 
-impl<'v> Value<'v> {
-    /// Try convert this value into an error.
-    #[cfg(feature = "kv_unstable_std")]
-    pub fn to_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        self.inner.to_error()
+    fn visit_empty(&mut self) -> Result<(), self::value_bag::Error> {
+        self.0 = Some(OwnedInner::None);
+        Ok(())
     }
 
-    /// Try convert this value into a borrowed string.
-    pub fn to_borrowed_str(&self) -> Option<&str> {
-        self.inner.to_borrowed_str()
+    fn visit_u64(&mut self, value: u64) -> Result<(), self::value_bag::Error> {
+        self.0 = Some(OwnedInner::U64(value));
+        Ok(())
     }
-}
 
-#[cfg(feature = "kv_unstable_std")]
-mod std_support {
-    use super::*;
+    fn visit_i64(&mut self, value: i64) -> Result<(), self::value_bag::Error> {
+        self.0 = Some(OwnedInner::I64(value));
+        Ok(())
+    }
 
-    use std::borrow::Cow;
+    fn visit_f64(&mut self, value: f64) -> Result<(), self::value_bag::Error> {
+        self.0 = Some(OwnedInner::F64(value));
+        Ok(())
+    }
 
-    impl<T> ToValue for Box<T>
+    fn visit_bool(&mut self, value: bool) -> Result<(), self::value_bag::Error> {
+        self.0 = Some(OwnedInner::Bool(value));
+        Ok(())
+    }
+
+    fn visit_char(&mut self, value: char) -> Result<(), self::value_bag::Error> {
+        self.0 = Some(OwnedInner::Char(value));
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &str) -> Result<(), self::value_bag::Error> {
+        self.0 = Some(OwnedInner::Str(value.to_owned()));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl ToValue for OwnedValue {
+    fn to_value(&self) -> Value {
+        match self.inner {
+            OwnedInner::None => Value::from_value_bag(()),
+            OwnedInner::Bool(value) => Value::from(value),
+            OwnedInner::Char(value) => Value::from(value),
+            OwnedInner::I64(value) => Value::from(value),
+            OwnedInner::U64(value) => Value::from(value),
+            OwnedInner::F64(value) => Value::from(value),
+            OwnedInner::Str(ref value) => Value::from(&**value),
+            OwnedInner::Source(ref pairs) => Value::from_source(pairs),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Fill for OwnedValue {
+    fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+        slot.fill(self.to_value())
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for OwnedValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_value(), f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for OwnedValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_value(), f)
+    }
+}
+
+/// The result of calling `value::variant`.
+#[derive(Debug)]
+pub struct Variant<T>(T);
+
+/// Capture just the name of an enum variant.
+///
+/// The value is rendered as the `Debug` output of `value` up to the first `(`, `{`,
+/// or whitespace character. So `Status::Active` and `Status::Failed(err)` are captured
+/// as `Active` and `Failed`. This is a best-effort textual extraction that relies on
+/// the shape of the default derived `Debug` implementation, so types with custom
+/// `Debug` implementations may not produce a meaningful name.
+pub fn variant<T>(value: T) -> Variant<T>
+where
+    T: fmt::Debug,
+{
+    Variant(value)
+}
+
+impl<T> fmt::Display for Variant<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct UntilFields<'a, 'b: 'a> {
+            f: &'a mut fmt::Formatter<'b>,
+            done: bool,
+        }
+
+        impl<'a, 'b> fmt::Write for UntilFields<'a, 'b> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                if self.done {
+                    return Ok(());
+                }
+
+                match s.find(|c: char| c == '(' || c == '{' || c.is_whitespace()) {
+                    Some(end) => {
+                        self.done = true;
+                        self.f.write_str(&s[..end])
+                    }
+                    None => self.f.write_str(s),
+                }
+            }
+        }
+
+        fmt::Write::write_fmt(
+            &mut UntilFields { f, done: false },
+            format_args!("{:?}", self.0),
+        )
+    }
+}
+
+impl<T> ToValue for Variant<T>
+where
+    T: fmt::Debug,
+{
+    fn to_value(&self) -> Value {
+        Value::from_display(self)
+    }
+}
+
+/// The result of calling `value::float_precision`.
+#[derive(Clone, Copy, Debug)]
+pub struct FloatPrecision {
+    value: f64,
+    digits: usize,
+}
+
+/// Capture a float that's rendered with a fixed number of decimal places.
+///
+/// The precision only applies when the value is formatted as text, so
+/// `float_precision(3.14159, 2)` is displayed as `3.14`. Structured backends,
+/// like `serde`, and conversions like `to_f64` still see the full `3.14159`.
+pub fn float_precision(value: f64, digits: usize) -> FloatPrecision {
+    FloatPrecision { value, digits }
+}
+
+impl ToValue for FloatPrecision {
+    fn to_value(&self) -> Value {
+        Value {
+            inner: Inner::Precision(ValueBag::from(self.value), self.digits),
+        }
+    }
+}
+
+/// The result of calling `value::bits`.
+#[derive(Clone, Copy, Debug)]
+pub struct Bits<'a> {
+    value: u64,
+    names: &'a [(u64, &'static str)],
+}
+
+/// Capture a set of bit flags as the names of the flags that are set.
+///
+/// A flag is set if all of its bits are set in `value`. Flags are listed in the
+/// order they appear in `names`, and any set bits without a name are ignored.
+/// Structured backends, like `serde`, see a sequence of names, and the value
+/// is formatted as text with the names comma-joined, like `read,exec`.
+///
+/// # Examples
+///
+/// ```
+/// use log::kv::{value, ToValue};
+///
+/// const FLAGS: &[(u64, &str)] = &[(0b001, "read"), (0b010, "write"), (0b100, "exec")];
+///
+/// let flags = value::bits(0b101, FLAGS);
+///
+/// assert_eq!("read,exec", flags.to_value().to_string());
+/// ```
+pub fn bits<'a>(value: u64, names: &'a [(u64, &'static str)]) -> Bits<'a> {
+    Bits { value, names }
+}
+
+impl<'a> ToValue for Bits<'a> {
+    fn to_value(&self) -> Value {
+        Value {
+            inner: Inner::Bits(self.value, self.names),
+        }
+    }
+}
+
+/// The result of calling `value::saturating`.
+#[derive(Clone, Copy, Debug)]
+pub struct Saturated<T> {
+    value: T,
+    did_saturate: bool,
+}
+
+/// Capture a number that may have been clamped by saturating arithmetic.
+///
+/// If `did_saturate` is `true` then the value is formatted as text with a
+/// marker, so `saturating(u8::MAX, true)` is displayed as `255 (saturated)`.
+/// Structured backends, like `serde`, and conversions like `to_u64` still see
+/// the plain `255`. If `did_saturate` is `false` then the value is captured
+/// as-is.
+///
+/// # Examples
+///
+/// ```
+/// use log::kv::{value, ToValue};
+///
+/// let (value, did_saturate) = match 200u8.checked_add(100) {
+///     Some(value) => (value, false),
+///     None => (u8::MAX, true),
+/// };
+///
+/// assert_eq!(
+///     "255 (saturated)",
+///     value::saturating(value, did_saturate).to_value().to_string()
+/// );
+/// ```
+pub fn saturating<T>(value: T, did_saturate: bool) -> Saturated<T>
+where
+    T: ToValue,
+{
+    Saturated {
+        value,
+        did_saturate,
+    }
+}
+
+impl<T> ToValue for Saturated<T>
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> Value {
+        let value = self.value.to_value();
+
+        match value.inner {
+            Inner::ValueBag(inner) if self.did_saturate => Value {
+                inner: Inner::Saturated(inner),
+            },
+            inner => Value { inner },
+        }
+    }
+}
+
+/// The result of calling `value::lazy`.
+pub struct Lazy<F>(F);
+
+/// Capture a value that's produced by calling a function when it's needed.
+///
+/// The function isn't called until the value is formatted or serialized, so
+/// fields that are expensive to compute are skipped when a record is filtered
+/// out, or a backend doesn't use them. It's called again each time the value
+/// is visited. This is a convenience over implementing
+/// [`Fill`](trait.Fill.html) by hand.
+///
+/// # Examples
+///
+/// ```
+/// use log::kv::{value, ToValue};
+///
+/// let answer = value::lazy(|| 6 * 7);
+///
+/// assert_eq!("42", answer.to_value().to_string());
+/// ```
+pub fn lazy<F, T>(f: F) -> Lazy<F>
+where
+    F: Fn() -> T,
+    T: ToValue,
+{
+    Lazy(f)
+}
+
+impl<F, T> Fill for Lazy<F>
+where
+    F: Fn() -> T,
+    T: ToValue,
+{
+    fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+        slot.fill((self.0)().to_value())
+    }
+}
+
+impl<F, T> ToValue for Lazy<F>
+where
+    F: Fn() -> T,
+    T: ToValue,
+{
+    fn to_value(&self) -> Value {
+        Value::from_fill(self)
+    }
+}
+
+impl<F> fmt::Debug for Lazy<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lazy").finish()
+    }
+}
+
+fn set_bits<'a>(
+    value: u64,
+    names: &'a [(u64, &'static str)],
+) -> impl Iterator<Item = &'static str> + 'a {
+    names
+        .iter()
+        .filter(move |&&(flag, _)| flag != 0 && value & flag == flag)
+        .map(|&(_, name)| name)
+}
+
+impl ToValue for str {
+    fn to_value(&self) -> Value {
+        Value::from(self)
+    }
+}
+
+// Values are compared with strings by their borrowed string, so values
+// captured using `Display` or `Debug` aren't equal to any string.
+impl<'v> PartialEq<str> for Value<'v> {
+    fn eq(&self, other: &str) -> bool {
+        self.to_borrowed_str() == Some(other)
+    }
+}
+
+impl<'a, 'v> PartialEq<&'a str> for Value<'v> {
+    fn eq(&self, other: &&'a str) -> bool {
+        *self == **other
+    }
+}
+
+impl<'v> From<&'v str> for Value<'v> {
+    fn from(value: &'v str) -> Self {
+        Value::from_value_bag(value)
+    }
+}
+
+impl ToValue for Duration {
+    fn to_value(&self) -> Value {
+        // Capturing the `Duration` itself lets structured backends downcast
+        // it to get at the whole seconds and nanoseconds. Everything else
+        // sees it as a number of seconds
+        Value::capture_debug(self)
+    }
+}
+
+impl ToValue for cmp::Ordering {
+    fn to_value(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+impl<'v> From<cmp::Ordering> for Value<'v> {
+    fn from(value: cmp::Ordering) -> Self {
+        Value::from(match value {
+            cmp::Ordering::Less => "Less",
+            cmp::Ordering::Equal => "Equal",
+            cmp::Ordering::Greater => "Greater",
+        })
+    }
+}
+
+impl ToValue for atomic::Ordering {
+    fn to_value(&self) -> Value {
+        match *self {
+            atomic::Ordering::Relaxed => Value::from("Relaxed"),
+            atomic::Ordering::Release => Value::from("Release"),
+            atomic::Ordering::Acquire => Value::from("Acquire"),
+            atomic::Ordering::AcqRel => Value::from("AcqRel"),
+            atomic::Ordering::SeqCst => Value::from("SeqCst"),
+            // The set of orderings isn't exhaustive
+            _ => Value::from_debug(self),
+        }
+    }
+}
+
+impl ToValue for () {
+    fn to_value(&self) -> Value {
+        Value::from_value_bag(())
+    }
+}
+
+impl<T> ToValue for Option<T>
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> Value {
+        match *self {
+            Some(ref value) => value.to_value(),
+            None => Value::from_value_bag(()),
+        }
+    }
+}
+
+macro_rules! impl_to_value_primitive {
+    ($($into_ty:ty,)*) => {
+        $(
+            impl ToValue for $into_ty {
+                fn to_value(&self) -> Value {
+                    Value::from(*self)
+                }
+            }
+
+            impl<'v> From<$into_ty> for Value<'v> {
+                fn from(value: $into_ty) -> Self {
+                    Value::from_value_bag(value)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_to_value_wrapping {
+    ($wrapper:ident: $($into_ty:ty,)*) => {
+        $(
+            impl ToValue for $wrapper<$into_ty> {
+                fn to_value(&self) -> Value {
+                    Value::from(*self)
+                }
+            }
+
+            impl<'v> From<$wrapper<$into_ty>> for Value<'v> {
+                fn from(value: $wrapper<$into_ty>) -> Self {
+                    Value::from(value.0)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_value_to_primitive {
+    ($(#[doc = $doc:tt] $into_name:ident -> $into_ty:ty,)*) => {
+        impl<'v> Value<'v> {
+            $(
+                #[doc = $doc]
+                pub fn $into_name(&self) -> Option<$into_ty> {
+                    if let Some(secs) = self.duration_secs() {
+                        return ValueBag::from(secs).$into_name();
+                    }
+
+                    match self.inner {
+                        Inner::Fill(ref fill) => with_filled(fill, |value| value.$into_name())
+                            .ok()
+                            .flatten(),
+                        _ => self.inner.value_bag().and_then(|inner| inner.$into_name()),
+                    }
+                }
+            )*
+        }
+
+        $(
+            impl FromValue for $into_ty {
+                fn from_value(value: &Value) -> Option<Self> {
+                    value.$into_name()
+                }
+            }
+
+            impl<'v> PartialEq<$into_ty> for Value<'v> {
+                fn eq(&self, other: &$into_ty) -> bool {
+                    self.$into_name() == Some(*other)
+                }
+            }
+        )*
+    }
+}
+
+impl_to_value_primitive![usize, u8, u16, u32, u64, isize, i8, i16, i32, i64, f32, f64, char, bool,];
+
+impl_to_value_wrapping![Wrapping: usize, u8, u16, u32, u64, isize, i8, i16, i32, i64,];
+impl_to_value_wrapping![Saturating: usize, u8, u16, u32, u64, isize, i8, i16, i32, i64,];
+
+impl_value_to_primitive![
+    #[doc = "Try convert this value into a `usize`."]
+    to_usize -> usize,
+    #[doc = "Try convert this value into a `u8`."]
+    to_u8 -> u8,
+    #[doc = "Try convert this value into a `u16`."]
+    to_u16 -> u16,
+    #[doc = "Try convert this value into a `u32`."]
+    to_u32 -> u32,
+    #[doc = "Try convert this value into a `u64`."]
+    to_u64 -> u64,
+    #[doc = "Try convert this value into a `isize`."]
+    to_isize -> isize,
+    #[doc = "Try convert this value into a `i8`."]
+    to_i8 -> i8,
+    #[doc = "Try convert this value into a `i16`."]
+    to_i16 -> i16,
+    #[doc = "Try convert this value into a `i32`."]
+    to_i32 -> i32,
+    #[doc = "Try convert this value into a `i64`."]
+    to_i64 -> i64,
+    #[doc = "Try convert this value into a `f32`."]
+    to_f32 -> f32,
+    #[doc = "Try convert this value into a `f64`."]
+    to_f64 -> f64,
+    #[doc = "Try convert this value into a `char`."]
+    to_char -> char,
+    #[doc = "Try convert this value into a `bool`."]
+    to_bool -> bool,
+];
+
+impl<'v> Value<'v> {
+    /// Try convert this value into an error.
+    #[cfg(feature = "kv_unstable_std")]
+    pub fn to_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner.value_bag().and_then(|inner| inner.to_error())
+    }
+
+    /// Check whether this value is empty, like `()` or `None`.
+    pub fn is_none(&self) -> bool {
+        match self.inner {
+            Inner::Fill(ref fill) => with_filled(fill, |value| value.is_none()).unwrap_or(false),
+            _ => self.inner.value_bag().is_some_and(|inner| inner.is_empty()),
+        }
+    }
+
+    /// Try convert this value into a borrowed string.
+    pub fn to_borrowed_str(&self) -> Option<&str> {
+        match self.inner {
+            #[cfg(feature = "std")]
+            Inner::Fill(FillRef::Owned(ref value)) => match value.inner {
+                OwnedInner::Str(ref value) => Some(value),
+                _ => None,
+            },
+            _ => self
+                .inner
+                .value_bag()
+                .and_then(|inner| inner.to_borrowed_str()),
+        }
+    }
+}
+
+#[cfg(feature = "kv_unstable_std")]
+mod std_support {
+    use super::*;
+
+    use std::borrow::Cow;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    impl<T> ToValue for Box<T>
+    where
+        T: ToValue + ?Sized,
+    {
+        fn to_value(&self) -> Value {
+            (**self).to_value()
+        }
+    }
+
+    impl<T> ToValue for Arc<T>
+    where
+        T: ToValue + ?Sized,
+    {
+        fn to_value(&self) -> Value {
+            (**self).to_value()
+        }
+    }
+
+    impl<T> ToValue for Rc<T>
     where
         T: ToValue + ?Sized,
     {
@@ -448,17 +1463,138 @@ mod std_support {
         }
     }
 
+    impl FromValue for String {
+        fn from_value(value: &Value) -> Option<Self> {
+            value.to_str().map(Cow::into_owned)
+        }
+    }
+
     impl<'v> ToValue for Cow<'v, str> {
         fn to_value(&self) -> Value {
             Value::from(&**self)
         }
     }
 
+    /// The result of calling `value::since`.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Since {
+        nanos: u64,
+    }
+
+    /// Capture the time elapsed between `base` and `instant`.
+    ///
+    /// An `Instant` can't be logged on its own because it has no absolute
+    /// representation, but the time between two of them can. The elapsed time
+    /// is captured as a number of nanoseconds. If `instant` is earlier than
+    /// `base` then the elapsed time is zero.
+    pub fn since(instant: Instant, base: Instant) -> Since {
+        let elapsed = instant.checked_duration_since(base).unwrap_or_default();
+
+        Since {
+            nanos: cmp::min(elapsed.as_nanos(), u128::from(u64::MAX)) as u64,
+        }
+    }
+
+    impl ToValue for Since {
+        fn to_value(&self) -> Value {
+            Value::from(self.nanos)
+        }
+    }
+
+    impl<'v> ToValue for Cow<'v, [u8]> {
+        fn to_value(&self) -> Value {
+            Value::from_bytes(self)
+        }
+    }
+
     impl<'v> Value<'v> {
         /// Try convert this value into a string.
         pub fn to_str(&self) -> Option<Cow<str>> {
-            self.inner.to_str()
+            match self.inner {
+                Inner::Fill(FillRef::Owned(ref value)) => match value.inner {
+                    OwnedInner::Str(ref value) => Some(Cow::Borrowed(value)),
+                    _ => None,
+                },
+                Inner::Fill(ref fill) => with_filled(fill, |value| {
+                    value.to_str().map(|value| Cow::Owned(value.into_owned()))
+                })
+                .ok()
+                .flatten(),
+                _ => self.inner.value_bag().and_then(|inner| inner.to_str()),
+            }
+        }
+
+        /// Render this value as a JSON scalar.
+        ///
+        /// Booleans, numbers, characters and strings are rendered as their JSON equivalents,
+        /// and an empty value like `()` or `None` is rendered as `null`. Any other value, such
+        /// as a nested source, is rendered as a JSON string containing its `Debug` form.
+        pub fn to_json_scalar(&self) -> String {
+            // Produce a filled value once instead of for each conversion
+            if let Inner::Fill(ref fill) = self.inner {
+                return with_filled(fill, |value| value.to_json_scalar())
+                    .unwrap_or_else(|_| "null".to_owned());
+            }
+
+            if let Some(value) = self.to_bool() {
+                return value.to_string();
+            }
+
+            if let Some(value) = self.to_f64() {
+                if !value.is_finite() {
+                    return "null".to_owned();
+                }
+
+                // Integers are also convertible to floats, so prefer their exact form
+                if let Some(int) = self.to_i64() {
+                    if int as f64 == value {
+                        return int.to_string();
+                    }
+                }
+                if let Some(int) = self.to_u64() {
+                    if int as f64 == value {
+                        return int.to_string();
+                    }
+                }
+
+                return value.to_string();
+            }
+
+            if let Some(value) = self.to_char() {
+                let mut buf = [0; 4];
+                return json_string(value.encode_utf8(&mut buf));
+            }
+
+            if let Some(value) = self.to_str() {
+                return json_string(&value);
+            }
+
+            if self.is_none() {
+                return "null".to_owned();
+            }
+
+            json_string(&format!("{:?}", self))
+        }
+    }
+
+    fn json_string(value: &str) -> String {
+        let mut json = String::with_capacity(value.len() + 2);
+
+        json.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                '\n' => json.push_str("\\n"),
+                '\r' => json.push_str("\\r"),
+                '\t' => json.push_str("\\t"),
+                c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+                c => json.push(c),
+            }
         }
+        json.push('"');
+
+        json
     }
 }
 
@@ -466,11 +1602,21 @@ mod std_support {
 pub(crate) mod tests {
     use super::*;
 
+    #[cfg(feature = "kv_unstable_serde")]
+    extern crate serde_test;
+
     pub(crate) use super::value_bag::test::Token;
 
     impl<'v> Value<'v> {
         pub(crate) fn to_token(&self) -> Token {
-            self.inner.to_token()
+            match self.inner {
+                Inner::ValueBag(ref inner)
+                | Inner::Precision(ref inner, _)
+                | Inner::Saturated(ref inner) => inner.to_token(),
+                Inner::Fill(ref fill) => with_filled(fill, |value| value.to_token())
+                    .unwrap_or_else(|err| Token::Str(err.to_string())),
+                _ => Token::Str(format!("{:?}", self)),
+            }
         }
     }
 
@@ -538,6 +1684,67 @@ pub(crate) mod tests {
         assert_eq!(Some(42u64), Value::from_serde(&42).to_u64());
     }
 
+    #[cfg(feature = "kv_unstable_serde")]
+    #[test]
+    fn test_from_serde_without_debug() {
+        use self::serde::Serialize;
+        use self::serde_test::{assert_ser_tokens, Token};
+
+        #[derive(Serialize)]
+        struct Point {
+            x: i32,
+        }
+
+        let point = Point { x: 1 };
+        let value = Value::from_serde(&point);
+
+        assert_ser_tokens(
+            &value,
+            &[
+                Token::Struct {
+                    name: "Point",
+                    len: 1,
+                },
+                Token::Str("x"),
+                Token::I32(1),
+                Token::StructEnd,
+            ],
+        );
+        assert!(!value.to_string().is_empty());
+    }
+
+    #[test]
+    fn test_capture_duration() {
+        let duration = Duration::new(1, 500);
+        let value = duration.to_value();
+
+        assert_eq!("1.0000005", value.to_string());
+        assert_eq!("1.0000005", format!("{:?}", value));
+        assert_eq!(Some(1.0000005), value.to_f64());
+        assert_eq!(Some(&duration), value.downcast_ref::<Duration>());
+    }
+
+    #[cfg(feature = "kv_unstable_serde")]
+    #[test]
+    fn test_serialize_duration() {
+        use self::serde_test::{assert_ser_tokens, Token};
+
+        assert_ser_tokens(
+            &Duration::new(1, 500).to_value(),
+            &[
+                Token::Struct {
+                    name: "Duration",
+                    len: 2,
+                },
+                Token::Str("secs"),
+                Token::U64(1),
+                Token::Str("nanos"),
+                Token::U32(500),
+                Token::StructEnd,
+            ],
+        );
+    }
+
     #[cfg(feature = "kv_unstable_sval")]
     #[test]
     fn test_capture_sval() {
@@ -546,6 +1753,39 @@ pub(crate) mod tests {
         assert_eq!(Some(42u64), Value::from_sval(&42).to_u64());
     }
 
+    #[cfg(feature = "kv_unstable_sval")]
+    #[test]
+    fn test_stream_sval() {
+        use self::sval::stream::{self, Stream};
+
+        #[derive(Default)]
+        struct Collect(Vec<String>);
+
+        impl Stream for Collect {
+            fn fmt(&mut self, v: stream::Arguments) -> stream::Result {
+                self.0.push(v.to_string());
+                Ok(())
+            }
+
+            fn u64(&mut self, v: u64) -> stream::Result {
+                self.0.push(format!("u64 {}", v));
+                Ok(())
+            }
+
+            fn str(&mut self, v: &str) -> stream::Result {
+                self.0.push(format!("str {}", v));
+                Ok(())
+            }
+        }
+
+        let mut collect = Collect::default();
+
+        Value::from(42u64).stream(&mut collect).unwrap();
+        Value::from("a string").stream(&mut collect).unwrap();
+
+        assert_eq!(vec!["u64 42", "str a string"], collect.0);
+    }
+
     #[test]
     fn test_to_value_display() {
         assert_eq!(42u64.to_value().to_string(), "42");
@@ -559,6 +1799,244 @@ pub(crate) mod tests {
         assert_eq!(Option::None::<bool>.to_value().to_string(), "None");
     }
 
+    #[test]
+    fn test_to_value_wrapping() {
+        assert_eq!(Wrapping(5u32).to_value().to_string(), "5");
+        assert_eq!(Wrapping(5u32).to_value().to_token(), Token::U64(5));
+        assert_eq!(Value::from(Wrapping(-5i8)).to_i64(), Some(-5));
+
+        let wrapped = Wrapping(u8::MAX) + Wrapping(1);
+        assert_eq!(wrapped.to_value().to_u8(), Some(0));
+    }
+
+    #[test]
+    fn test_to_value_saturating() {
+        assert_eq!(Saturating(u8::MAX).to_value().to_string(), "255");
+        assert_eq!(Saturating(u8::MAX).to_value().to_token(), Token::U64(255));
+        assert_eq!(Value::from(Saturating(-5i8)).to_i64(), Some(-5));
+
+        let saturated = Saturating(u8::MAX) + Saturating(1);
+        assert_eq!(saturated.to_value().to_u8(), Some(u8::MAX));
+    }
+
+    #[test]
+    fn test_from_any_dyn() {
+        struct Unknown;
+
+        let int: &dyn Any = &42i32;
+        let string: &dyn Any = &"a string";
+        let unknown: &dyn Any = &Unknown;
+
+        assert_eq!(Token::I64(42), Value::from_any_dyn(int).to_token());
+        assert_eq!(
+            Token::Str("a string".into()),
+            Value::from_any_dyn(string).to_token()
+        );
+        assert_eq!(
+            Some("<unknown>"),
+            Value::from_any_dyn(unknown).to_borrowed_str()
+        );
+    }
+
+    #[test]
+    fn test_to_value_ordering() {
+        assert_eq!(
+            Some("Less"),
+            cmp::Ordering::Less.to_value().to_borrowed_str()
+        );
+        assert_eq!(Some("Greater"), Value::from(1.cmp(&0)).to_borrowed_str());
+        assert_eq!(
+            Some("SeqCst"),
+            atomic::Ordering::SeqCst.to_value().to_borrowed_str()
+        );
+    }
+
+    #[test]
+    fn test_variant() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        enum Status {
+            Active,
+            Failed(&'static str),
+            Retrying { attempt: u32 },
+        }
+
+        assert_eq!(variant(Status::Active).to_value().to_string(), "Active");
+        assert_eq!(
+            variant(Status::Failed("timeout")).to_value().to_string(),
+            "Failed"
+        );
+        assert_eq!(
+            variant(Status::Retrying { attempt: 1 })
+                .to_value()
+                .to_string(),
+            "Retrying"
+        );
+    }
+
+    #[test]
+    fn test_float_precision() {
+        let value = float_precision(1.23456, 2);
+
+        assert_eq!("1.23", value.to_value().to_string());
+        assert_eq!(Some(1.23456), value.to_value().to_f64());
+        assert_eq!(Token::F64(1.23456), value.to_value().to_token());
+    }
+
+    #[test]
+    fn test_saturating() {
+        let saturated = saturating(u8::MAX, true);
+
+        assert_eq!("255 (saturated)", saturated.to_value().to_string());
+        assert_eq!(Some(255), saturated.to_value().to_u64());
+        assert_eq!(Token::U64(255), saturated.to_value().to_token());
+
+        assert_eq!("255", saturating(u8::MAX, false).to_value().to_string());
+    }
+
+    #[test]
+    fn test_bits() {
+        let names = [(0b001, "read"), (0b010, "write"), (0b100, "exec")];
+        let value = bits(0b101, &names);
+
+        assert_eq!("read,exec", value.to_value().to_string());
+        assert_eq!("[\"read\", \"exec\"]", format!("{:?}", value.to_value()));
+        assert_eq!("", bits(0, &names).to_value().to_string());
+    }
+
+    #[test]
+    fn test_from_str_slice() {
+        let tags = ["a", "b"];
+
+        assert_eq!("[\"a\", \"b\"]", Value::from_str_slice(&tags).to_string());
+    }
+
+    #[cfg(feature = "kv_unstable_serde")]
+    #[test]
+    fn test_serialize_str_slice() {
+        use self::serde_test::{assert_ser_tokens, Token};
+
+        assert_ser_tokens(
+            &Value::from_str_slice(&["a", "b"]),
+            &[
+                Token::Seq { len: Some(2) },
+                Token::Str("a"),
+                Token::Str("b"),
+                Token::SeqEnd,
+            ],
+        );
+    }
+
+    #[cfg(feature = "kv_unstable_serde")]
+    #[test]
+    fn test_serialize_seq() {
+        use self::serde_test::{assert_ser_tokens, Token};
+
+        let value = Value::from_seq(vec![Value::from("a"), Value::from(1i64)]);
+
+        assert_eq!(r#"["a", 1]"#, format!("{:?}", value));
+        assert_ser_tokens(
+            &value,
+            &[
+                Token::Seq { len: Some(2) },
+                Token::Str("a"),
+                Token::I64(1),
+                Token::SeqEnd,
+            ],
+        );
+    }
+
+    #[cfg(feature = "kv_unstable_serde")]
+    #[test]
+    fn test_serialize_bits() {
+        use self::serde_test::{assert_ser_tokens, Token};
+
+        let names = [(0b001, "read"), (0b010, "write"), (0b100, "exec")];
+
+        assert_ser_tokens(
+            &bits(0b101, &names).to_value(),
+            &[
+                Token::Seq { len: None },
+                Token::Str("read"),
+                Token::Str("exec"),
+                Token::SeqEnd,
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable_std")]
+    fn test_since() {
+        use std::time::Instant;
+
+        let base = Instant::now();
+        let later = base + Duration::from_millis(5);
+
+        assert_eq!(Some(5_000_000), since(later, base).to_value().to_u64());
+        assert_eq!(Some(0), since(base, later).to_value().to_u64());
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable_std")]
+    fn test_to_value_smart_pointers() {
+        use std::rc::Rc;
+        use std::sync::Arc;
+
+        assert_eq!(Some(5), Box::new(5i32).to_value().to_i32());
+        assert_eq!(Token::Str("s".into()), Arc::new("s").to_value().to_token());
+        assert_eq!(Some(true), Rc::new(true).to_value().to_bool());
+
+        let unsized_value: Arc<str> = Arc::from("unsized");
+        assert_eq!("unsized", unsized_value.to_value().to_string());
+    }
+
+    #[test]
+    fn test_eq_primitive() {
+        assert_eq!(Value::from(5i32), 5i32);
+        assert_eq!(Value::from(5u8), 5i64);
+        assert_eq!(Value::from(1.5f64), 1.5f64);
+        assert_eq!(Value::from(true), true);
+        assert_eq!(Value::from('a'), 'a');
+        assert_eq!(Value::from("login"), "login");
+
+        assert_ne!(Value::from(5i32), 6i32);
+        assert_ne!(Value::from(true), false);
+        assert_ne!(Value::from("login"), "logout");
+        assert_ne!(Value::from("5"), 5i32);
+        assert_ne!(Value::from_display(&"login"), "login");
+    }
+
+    #[test]
+    fn test_from_value() {
+        assert_eq!(Some(42i64), i64::from_value(&Value::from(42u8)));
+        assert_eq!(Some(true), bool::from_value(&Value::from(true)));
+        assert_eq!(None, bool::from_value(&Value::from("true")));
+
+        #[cfg(feature = "kv_unstable_std")]
+        assert_eq!(
+            Some("a string".to_owned()),
+            String::from_value(&Value::from("a string"))
+        );
+    }
+
+    #[test]
+    fn test_from_dyn() {
+        let borrowed = String::from("a borrowed string");
+        let display: &dyn fmt::Display = &borrowed;
+        let debug: &dyn fmt::Debug = &borrowed;
+
+        assert_eq!(
+            Value::from_dyn_display(display).to_string(),
+            "a borrowed string"
+        );
+        assert_eq!(
+            Value::from_dyn_debug(debug).to_string(),
+            "\"a borrowed string\""
+        );
+        assert_eq!(display.to_value().to_string(), "a borrowed string");
+        assert!(Value::from_dyn_display(display).to_borrowed_str().is_none());
+    }
+
     #[test]
     fn test_to_value_structured() {
         assert_eq!(42u64.to_value().to_token(), Token::U64(42));
@@ -667,6 +2145,207 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "kv_unstable_std")]
+    fn test_to_value_cow() {
+        use std::borrow::Cow;
+
+        let borrowed: Cow<str> = Cow::Borrowed("a string");
+        let owned: Cow<str> = Cow::Owned(String::from("a string"));
+
+        for cow in &[borrowed, owned] {
+            assert_eq!(Some("a string"), cow.to_value().to_borrowed_str());
+            assert_eq!(Token::Str("a string".into()), cow.to_value().to_token());
+        }
+
+        let borrowed: Cow<[u8]> = Cow::Borrowed(b"hi");
+        let owned: Cow<[u8]> = Cow::Owned(b"hi".to_vec());
+
+        for cow in &[borrowed, owned] {
+            assert_eq!("[104, 105]", cow.to_value().to_string());
+        }
+    }
+
+    #[cfg(feature = "kv_unstable_serde")]
+    #[test]
+    fn test_serialize_bytes() {
+        use self::serde_test::{assert_ser_tokens, Token};
+        use std::borrow::Cow;
+
+        let borrowed: Cow<[u8]> = Cow::Borrowed(b"hi");
+        let owned: Cow<[u8]> = Cow::Owned(b"hi".to_vec());
+
+        for cow in &[borrowed, owned] {
+            assert_ser_tokens(&cow.to_value(), &[Token::Bytes(b"hi")]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable_std")]
+    fn test_to_owned_value() {
+        let text = String::from("a string");
+
+        for value in vec![
+            Value::from(true),
+            Value::from('a'),
+            Value::from(-1i64),
+            Value::from(1i64),
+            Value::from(1u64),
+            Value::from(u64::MAX),
+            Value::from(1.5f64),
+            Value::from(2.0f64),
+            Value::from(&*text),
+            Value::from_value_bag(()),
+        ] {
+            assert_eq!(
+                value.to_token(),
+                value.to_owned_value().to_value().to_token()
+            );
+        }
+
+        let owned = Value::from_display(&42).to_owned_value();
+        assert_eq!(Some("42"), owned.to_value().to_borrowed_str());
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable_std")]
+    fn test_to_json_scalar() {
+        assert_eq!("42", Value::from(42u8).to_json_scalar());
+        assert_eq!("-42", Value::from(-42i64).to_json_scalar());
+        assert_eq!(
+            "18446744073709551615",
+            Value::from(u64::MAX).to_json_scalar()
+        );
+        assert_eq!("64.64", Value::from(64.64f64).to_json_scalar());
+        assert_eq!("null", Value::from(f64::NAN).to_json_scalar());
+
+        assert_eq!("true", Value::from(true).to_json_scalar());
+        assert_eq!("false", Value::from(false).to_json_scalar());
+
+        assert_eq!("null", ().to_value().to_json_scalar());
+        assert_eq!("null", Option::None::<bool>.to_value().to_json_scalar());
+
+        #[derive(Debug)]
+        struct None;
+        assert_eq!(r#""None""#, Value::from_debug(&None).to_json_scalar());
+
+        assert_eq!(r#""a""#, Value::from('a').to_json_scalar());
+        assert_eq!(
+            r#""an \"escaped\" \\ string\n\u0001""#,
+            Value::from("an \"escaped\" \\ string\n\u{1}").to_json_scalar()
+        );
+        assert_eq!(
+            r#""Some(\"bar\")""#,
+            Value::from_debug(&Some("bar")).to_json_scalar()
+        );
+    }
+
+    #[test]
+    fn test_fill_is_lazy() {
+        use std::cell::Cell;
+
+        struct Expensive(Cell<usize>);
+
+        impl Fill for Expensive {
+            fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+                self.0.set(self.0.get() + 1);
+                slot.fill_any(42)
+            }
+        }
+
+        let expensive = Expensive(Cell::new(0));
+        let source = ("a", Value::from_fill(&expensive));
+        assert_eq!(0, expensive.0.get());
+
+        assert_eq!(r#"{"a": 42}"#, format!("{:?}", source::as_map(&source)));
+        assert_eq!(1, expensive.0.get());
+    }
+
+    #[test]
+    fn test_is_none() {
+        assert!(().to_value().is_none());
+        assert!(Option::None::<u8>.to_value().is_none());
+
+        assert!(!Some(0u8).to_value().is_none());
+        assert!(!Value::from("").is_none());
+        assert!(!Value::from_debug(&Option::None::<u8>).is_none());
+    }
+
+    #[test]
+    fn test_fill_casts() {
+        struct Answer;
+
+        impl Fill for Answer {
+            fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+                slot.fill_any(42)
+            }
+        }
+
+        let value = Value::from_fill(&Answer);
+
+        assert_eq!(Some(42), value.to_i64());
+        assert_eq!(Some(42.0), value.to_f64());
+        assert_eq!(None, value.to_bool());
+        assert!(value == 42u8);
+        assert_eq!(Some(42), source::get_as::<_, _, u32>(&("a", value), "a"));
+
+        #[cfg(feature = "kv_unstable_std")]
+        {
+            struct Name;
+
+            impl Fill for Name {
+                fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+                    slot.fill_any("alice")
+                }
+            }
+
+            assert_eq!("42", Value::from_fill(&Answer).to_json_scalar());
+            assert_eq!(Some("alice"), Value::from_fill(&Name).to_str().as_deref());
+            assert_eq!(r#""alice""#, Value::from_fill(&Name).to_json_scalar());
+        }
+    }
+
+    #[test]
+    fn test_lazy() {
+        use std::cell::Cell;
+
+        let calls = Cell::new(0);
+        let value = lazy(|| {
+            calls.set(calls.get() + 1);
+            42
+        });
+
+        let source = ("a", value.to_value());
+        assert_eq!(0, calls.get());
+
+        assert_eq!(r#"{"a": 42}"#, format!("{:?}", source::as_map(&source)));
+        assert_eq!(1, calls.get());
+    }
+
+    #[test]
+    fn test_fill_twice() {
+        struct Twice;
+
+        impl Fill for Twice {
+            fn fill(&self, slot: &mut Slot) -> Result<(), Error> {
+                slot.fill_any(1)?;
+                assert!(slot.fill_any(2).is_err());
+                Ok(())
+            }
+        }
+
+        struct Empty;
+
+        impl Fill for Empty {
+            fn fill(&self, _: &mut Slot) -> Result<(), Error> {
+                Ok(())
+            }
+        }
+
+        assert_eq!("1", Value::from_fill(&Twice).to_string());
+        assert_eq!("None", Value::from_fill(&Empty).to_string());
+    }
+
     #[test]
     fn test_downcast_ref() {
         #[derive(Debug)]
@@ -0,0 +1,115 @@
+//! A logger that remembers the last error.
+
+use std::sync::Mutex;
+
+use {Level, Log, Metadata, Record};
+
+/// A logger that remembers where the most recent `Error` record was logged.
+///
+/// Records are forwarded to the inner logger unchanged. The file, line, and
+/// message of the last `Error` record are kept, so code like a panic hook or
+/// crash reporter can include them for context. Records without a file are
+/// remembered with `<unknown>` as their file, and records without a line are
+/// remembered with `0` as their line.
+///
+/// Requires the `std` feature.
+#[derive(Debug)]
+pub struct LastError<L> {
+    inner: L,
+    last: Mutex<Option<(String, u32, String)>>,
+}
+
+impl<L> LastError<L>
+where
+    L: Log,
+{
+    /// Wrap a logger.
+    pub fn new(inner: L) -> Self {
+        LastError {
+            inner,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// The file, line, and message of the most recent `Error` record.
+    ///
+    /// This is `None` if no errors have been logged.
+    pub fn last_error(&self) -> Option<(String, u32, String)> {
+        self.last
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+impl<L> Log for LastError<L>
+where
+    L: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() == Level::Error {
+            let last = (
+                record.file().unwrap_or("<unknown>").to_owned(),
+                record.line().unwrap_or(0),
+                record.args().to_string(),
+            );
+
+            *self.last.lock().unwrap_or_else(|err| err.into_inner()) = Some(last);
+        }
+
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Discard;
+
+    impl Log for Discard {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, _: &Record) {}
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn last_error_records_location() {
+        let logger = LastError::new(Discard);
+        assert_eq!(None, logger.last_error());
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Error)
+                .file(Some("src/db.rs"))
+                .line(Some(42))
+                .args(format_args!("connection lost"))
+                .build(),
+        );
+        logger.log(
+            &Record::builder()
+                .level(Level::Warn)
+                .file(Some("src/main.rs"))
+                .line(Some(7))
+                .args(format_args!("retrying"))
+                .build(),
+        );
+
+        assert_eq!(
+            Some(("src/db.rs".to_owned(), 42, "connection lost".to_owned())),
+            logger.last_error()
+        );
+    }
+}
@@ -296,6 +296,76 @@ mod serde;
 #[cfg(feature = "kv_unstable")]
 pub mod kv;
 
+#[cfg(feature = "std")]
+mod channel;
+#[cfg(feature = "std")]
+mod coalesce;
+#[cfg(feature = "std")]
+mod fan_out;
+#[cfg(feature = "std")]
+mod filtered;
+#[cfg(feature = "std")]
+mod flush_on_drop;
+#[cfg(feature = "std")]
+mod last_error;
+#[cfg(feature = "std")]
+mod log_ext;
+#[cfg(feature = "std")]
+mod owned;
+#[cfg(feature = "std")]
+mod panic_on_error;
+#[cfg(feature = "std")]
+mod rate_limit;
+#[cfg(feature = "std")]
+mod reload;
+#[cfg(feature = "std")]
+mod sampler;
+#[cfg(all(feature = "std", feature = "kv_unstable"))]
+mod schema;
+#[cfg(feature = "std")]
+mod timestamp;
+#[cfg(all(feature = "std", feature = "kv_unstable"))]
+mod with_fields;
+#[cfg(feature = "std")]
+mod write_logger;
+
+#[cfg(feature = "std")]
+pub use channel::ChannelLogger;
+#[cfg(feature = "std")]
+pub use coalesce::Coalesce;
+#[cfg(feature = "std")]
+pub use fan_out::FanOut;
+#[cfg(feature = "std")]
+pub use filtered::Filtered;
+#[cfg(feature = "std")]
+pub use flush_on_drop::FlushOnDrop;
+#[cfg(feature = "std")]
+pub use last_error::LastError;
+#[cfg(feature = "std")]
+pub use log_ext::LogExt;
+#[cfg(feature = "std")]
+pub use owned::OwnedRecord;
+#[cfg(feature = "std")]
+pub use panic_on_error::PanicOnError;
+#[cfg(feature = "std")]
+pub use rate_limit::RateLimited;
+#[cfg(all(feature = "std", atomic_cas))]
+pub use reload::init_reloadable;
+#[cfg(feature = "std")]
+pub use reload::ReloadHandle;
+#[cfg(feature = "std")]
+pub use sampler::Sampler;
+#[cfg(all(feature = "std", feature = "kv_unstable", atomic_cas))]
+pub use schema::init_with_schema;
+#[cfg(all(feature = "std", feature = "kv_unstable"))]
+pub use schema::SchemaLogger;
+#[cfg(feature = "std")]
+pub use timestamp::{Clock, SystemClock, TimestampLogger};
+#[cfg(all(feature = "std", feature = "kv_unstable"))]
+pub use with_fields::WithFields;
+#[cfg(feature = "std")]
+pub use write_logger::WriteLogger;
+
 #[cfg(has_atomics)]
 use std::sync::atomic::{AtomicUsize, Ordering};
 
@@ -337,6 +407,13 @@ impl AtomicUsize {
         }
         Ok(prev)
     }
+
+    #[cfg(atomic_cas)]
+    fn fetch_add(&self, val: usize, _order: Ordering) -> usize {
+        let prev = self.v.get();
+        self.v.set(prev.wrapping_add(val));
+        prev
+    }
 }
 
 // Any platform without atomics is unlikely to have multiple cores, so
@@ -359,6 +436,12 @@ const INITIALIZED: usize = 2;
 
 static MAX_LOG_LEVEL_FILTER: AtomicUsize = AtomicUsize::new(0);
 
+static MAX_LOG_LEVEL_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+// The syslog severity of each level, indexed by its discriminant. `Off` has
+// no severity, so its slot is unused
+static SYSLOG_SEVERITIES: [u8; 6] = [0, 3, 4, 6, 7, 7];
+
 static LOG_LEVEL_NAMES: [&str; 6] = ["OFF", "ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
 
 static SET_LOGGER_ERROR: &str = "attempted to set a logger after the logging system \
@@ -373,7 +456,7 @@ static LEVEL_PARSE_ERROR: &str =
 /// [`log!`](macro.log.html), and comparing a `Level` directly to a
 /// [`LevelFilter`](enum.LevelFilter.html).
 #[repr(usize)]
-#[derive(Copy, Eq, Debug, Hash)]
+#[derive(Copy, Eq, Hash)]
 pub enum Level {
     /// The "error" level.
     ///
@@ -530,6 +613,28 @@ impl fmt::Display for Level {
     }
 }
 
+// The alternate `{:#?}` form is the lowercase name, like `error`.
+impl fmt::Debug for Level {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let (name, lower) = match *self {
+            Level::Error => ("Error", "error"),
+            Level::Warn => ("Warn", "warn"),
+            Level::Info => ("Info", "info"),
+            Level::Debug => ("Debug", "debug"),
+            Level::Trace => ("Trace", "trace"),
+        };
+
+        fmt.write_str(if fmt.alternate() { lower } else { name })
+    }
+}
+
+impl From<Level> for u8 {
+    /// Convert a level into its numeric value, from `1` for `Error` to `5` for `Trace`.
+    fn from(level: Level) -> u8 {
+        level as u8
+    }
+}
+
 impl Level {
     fn from_usize(u: usize) -> Option<Level> {
         match u {
@@ -560,6 +665,133 @@ impl Level {
     pub fn as_str(&self) -> &'static str {
         LOG_LEVEL_NAMES[*self as usize]
     }
+
+    /// Returns the name of the `Level` padded to five characters, followed by a space.
+    ///
+    /// This is a fixed-width prefix for a column of levels, like `"INFO  "`,
+    /// that doesn't need to be formatted for each record.
+    pub fn prefix_padded(&self) -> &'static str {
+        match *self {
+            Level::Error => "ERROR ",
+            Level::Warn => "WARN  ",
+            Level::Info => "INFO  ",
+            Level::Debug => "DEBUG ",
+            Level::Trace => "TRACE ",
+        }
+    }
+
+    /// Returns the syslog severity of the `Level`.
+    ///
+    /// Levels are mapped to syslog severities as:
+    ///
+    /// | `Level` | Severity      |
+    /// | ------- | ------------- |
+    /// | `Error` | `3` (err)     |
+    /// | `Warn`  | `4` (warning) |
+    /// | `Info`  | `6` (info)    |
+    /// | `Debug` | `7` (debug)   |
+    /// | `Trace` | `7` (debug)   |
+    ///
+    /// Syslog has no severity below debug, so `Trace` shares it with `Debug`.
+    pub fn to_syslog_severity(&self) -> u8 {
+        SYSLOG_SEVERITIES[*self as usize]
+    }
+
+    /// Returns the `Level` for a syslog severity.
+    ///
+    /// This is the inverse of [`to_syslog_severity`](#method.to_syslog_severity).
+    /// The severities without their own level are mapped to the nearest one, so
+    /// emerg (`0`), alert (`1`), and crit (`2`) are `Error`, and notice (`5`) is `Info`.
+    /// Debug (`7`) is `Debug`, as is any number above it.
+    pub fn from_syslog_severity(severity: u8) -> Level {
+        // Find the most severe level that isn't more severe than `severity`,
+        // clamping anything past the least severe level to it
+        let severity = cmp::min(severity, SYSLOG_SEVERITIES[Level::max() as usize]);
+
+        (1..SYSLOG_SEVERITIES.len())
+            .find(|&i| SYSLOG_SEVERITIES[i] >= severity)
+            .and_then(Level::from_usize)
+            .unwrap_or(Level::max())
+    }
+
+    /// Returns the GELF level of the `Level`.
+    ///
+    /// GELF uses syslog severities, so this is the same as
+    /// [`to_syslog_severity`](#method.to_syslog_severity).
+    pub fn to_gelf_level(&self) -> u8 {
+        self.to_syslog_severity()
+    }
+
+    /// Returns the `Level` for a GELF level.
+    ///
+    /// GELF uses syslog severities, so this is the same as
+    /// [`from_syslog_severity`](#method.from_syslog_severity).
+    pub fn from_gelf_level(level: u8) -> Level {
+        Level::from_syslog_severity(level)
+    }
+
+    /// Returns a wrapper that serializes the `Level` in lowercase, like `"info"`.
+    ///
+    /// `Level`'s own `Serialize` implementation uses uppercase names.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub fn as_serialize_lower(&self) -> LowercaseLevel {
+        LowercaseLevel(*self)
+    }
+
+    /// Returns a wrapper that serializes the `Level` as its number, like `3`.
+    ///
+    /// `Level`'s own `Serialize` implementation uses names.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub fn as_serialize_numeric(&self) -> NumericLevel {
+        NumericLevel(*self)
+    }
+}
+
+/// A `Level` that serializes in lowercase.
+///
+/// This type is returned by [`Level::as_serialize_lower`](enum.Level.html#method.as_serialize_lower).
+#[cfg(feature = "serde")]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct LowercaseLevel(Level);
+
+/// A `Level` that serializes as its number, from `1` for `Error` to `5` for `Trace`.
+///
+/// `Level`'s own `Serialize` and `Deserialize` implementations use names. This
+/// wrapper can be used for formats that store levels as numbers instead.
+///
+/// This type is returned by [`Level::as_serialize_numeric`](enum.Level.html#method.as_serialize_numeric).
+#[cfg(feature = "serde")]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct NumericLevel(Level);
+
+#[cfg(feature = "serde")]
+impl NumericLevel {
+    /// Returns the wrapped `Level`.
+    #[inline]
+    pub fn level(&self) -> Level {
+        self.0
+    }
+}
+
+/// A `LevelFilter` that serializes as its number, from `0` for `Off` to `5` for `Trace`.
+///
+/// `LevelFilter`'s own `Serialize` and `Deserialize` implementations use names.
+/// This wrapper can be used for formats that store level filters as numbers instead.
+///
+/// This type is returned by [`LevelFilter::as_serialize_numeric`](enum.LevelFilter.html#method.as_serialize_numeric).
+#[cfg(feature = "serde")]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct NumericLevelFilter(LevelFilter);
+
+#[cfg(feature = "serde")]
+impl NumericLevelFilter {
+    /// Returns the wrapped `LevelFilter`.
+    #[inline]
+    pub fn level_filter(&self) -> LevelFilter {
+        self.0
+    }
 }
 
 /// An enum representing the available verbosity level filters of the logger.
@@ -571,7 +803,7 @@ impl Level {
 /// [`max_level()`]: fn.max_level.html
 /// [`set_max_level`]: fn.set_max_level.html
 #[repr(usize)]
-#[derive(Copy, Eq, Debug, Hash)]
+#[derive(Copy, Eq, Hash)]
 pub enum LevelFilter {
     /// A level lower than all log levels.
     Off,
@@ -690,6 +922,29 @@ impl fmt::Display for LevelFilter {
     }
 }
 
+// The alternate `{:#?}` form is the lowercase name, like `off`.
+impl fmt::Debug for LevelFilter {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let (name, lower) = match *self {
+            LevelFilter::Off => ("Off", "off"),
+            LevelFilter::Error => ("Error", "error"),
+            LevelFilter::Warn => ("Warn", "warn"),
+            LevelFilter::Info => ("Info", "info"),
+            LevelFilter::Debug => ("Debug", "debug"),
+            LevelFilter::Trace => ("Trace", "trace"),
+        };
+
+        fmt.write_str(if fmt.alternate() { lower } else { name })
+    }
+}
+
+impl From<LevelFilter> for u8 {
+    /// Convert a level filter into its numeric value, from `0` for `Off` to `5` for `Trace`.
+    fn from(filter: LevelFilter) -> u8 {
+        filter as u8
+    }
+}
+
 impl LevelFilter {
     fn from_usize(u: usize) -> Option<LevelFilter> {
         match u {
@@ -722,6 +977,185 @@ impl LevelFilter {
     pub fn as_str(&self) -> &'static str {
         LOG_LEVEL_NAMES[*self as usize]
     }
+
+    /// Returns a wrapper that serializes the `LevelFilter` as its number, like `3`.
+    ///
+    /// `LevelFilter`'s own `Serialize` implementation uses names.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub fn as_serialize_numeric(&self) -> NumericLevelFilter {
+        NumericLevelFilter(*self)
+    }
+
+    /// Returns the next most verbose filter.
+    ///
+    /// This saturates at `LevelFilter::Trace`, so it can be called once
+    /// for each `-v` flag passed to a command-line application.
+    #[inline]
+    pub fn more_verbose(self) -> LevelFilter {
+        LevelFilter::from_usize(self as usize + 1).unwrap_or(LevelFilter::Trace)
+    }
+
+    /// Returns the next least verbose filter.
+    ///
+    /// This saturates at `LevelFilter::Off`, so it can be called once
+    /// for each `-q` flag passed to a command-line application.
+    #[inline]
+    pub fn less_verbose(self) -> LevelFilter {
+        match self {
+            LevelFilter::Off => LevelFilter::Off,
+            filter => LevelFilter::from_usize(filter as usize - 1).unwrap(),
+        }
+    }
+
+    /// Read a level filter from an environment variable.
+    ///
+    /// The variable can contain a level name, like `debug`, in any case, or
+    /// a number from `0` for `Off` to `5` for `Trace`. If the variable isn't
+    /// set, or can't be parsed, then `default` is returned.
+    ///
+    /// Requires the `std` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// use log::LevelFilter;
+    ///
+    /// log::set_max_level(LevelFilter::from_env("MY_APP_LOG", LevelFilter::Info));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_env(var: &str, default: LevelFilter) -> LevelFilter {
+        let value = match std::env::var(var) {
+            Ok(value) => value,
+            Err(_) => return default,
+        };
+        let value = value.trim();
+
+        value
+            .parse()
+            .ok()
+            .or_else(|| value.parse().ok().and_then(LevelFilter::from_usize))
+            .unwrap_or(default)
+    }
+}
+
+/// A set of [`Level`]s.
+///
+/// A [`LevelFilter`] enables a contiguous range of levels, from `Error` up to
+/// some threshold. A `LevelSet` can enable any combination of levels, such
+/// as `Error` and `Trace` but nothing in between.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use log::{Level, LevelSet};
+///
+/// let mut set = LevelSet::empty();
+/// set.insert(Level::Error);
+/// set.insert(Level::Trace);
+///
+/// assert!(set.contains(Level::Error));
+/// assert!(!set.contains(Level::Info));
+/// ```
+///
+/// [`Level`]: enum.Level.html
+/// [`LevelFilter`]: enum.LevelFilter.html
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct LevelSet(u8);
+
+impl LevelSet {
+    /// Returns a set that contains no levels.
+    #[inline]
+    pub fn empty() -> LevelSet {
+        LevelSet(0)
+    }
+
+    /// Returns the set of levels that are enabled by the given `LevelFilter`.
+    ///
+    /// `LevelFilter::Off` produces an empty set.
+    #[inline]
+    pub fn from_filter(filter: LevelFilter) -> LevelSet {
+        // Every level up to and including the filter's discriminant is enabled.
+        LevelSet(((1usize << (filter as usize + 1)) - 2) as u8)
+    }
+
+    /// Adds a level to the set.
+    #[inline]
+    pub fn insert(&mut self, level: Level) {
+        self.0 |= LevelSet::bit(level);
+    }
+
+    /// Returns `true` if the set contains the given level.
+    #[inline]
+    pub fn contains(&self, level: Level) -> bool {
+        self.0 & LevelSet::bit(level) != 0
+    }
+
+    /// Returns `true` if the set contains no levels.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns an iterator over the levels in the set.
+    ///
+    /// Levels are yielded from most to least severe.
+    #[inline]
+    pub fn iter(&self) -> LevelSetIter {
+        LevelSetIter {
+            set: *self,
+            next: Level::Error as usize,
+        }
+    }
+
+    #[inline]
+    fn bit(level: Level) -> u8 {
+        1 << (level as usize)
+    }
+}
+
+impl From<LevelFilter> for LevelSet {
+    fn from(filter: LevelFilter) -> Self {
+        LevelSet::from_filter(filter)
+    }
+}
+
+impl fmt::Debug for LevelSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl IntoIterator for LevelSet {
+    type Item = Level;
+    type IntoIter = LevelSetIter;
+
+    fn into_iter(self) -> LevelSetIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the levels in a [`LevelSet`](struct.LevelSet.html).
+#[derive(Clone, Debug)]
+pub struct LevelSetIter {
+    set: LevelSet,
+    next: usize,
+}
+
+impl Iterator for LevelSetIter {
+    type Item = Level;
+
+    fn next(&mut self) -> Option<Level> {
+        while let Some(level) = Level::from_usize(self.next) {
+            self.next += 1;
+
+            if self.set.contains(level) {
+                return Some(level);
+            }
+        }
+
+        None
+    }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -792,6 +1226,9 @@ pub struct Record<'a> {
     module_path: Option<MaybeStaticStr<'a>>,
     file: Option<MaybeStaticStr<'a>>,
     line: Option<u32>,
+    timestamp: Option<u64>,
+    span_id: Option<u64>,
+    trace_id: Option<u128>,
     #[cfg(feature = "kv_unstable")]
     key_values: KeyValues<'a>,
 }
@@ -800,15 +1237,72 @@ pub struct Record<'a> {
 // `#[derive(Debug)]` on `Record`. It also
 // provides a useful `Debug` implementation for
 // the underlying `Source`.
+//
+// Chained sources are stored as a linked list,
+// with each link sharing the chain before it, so
+// building a record never copies the chain.
 #[cfg(feature = "kv_unstable")]
 #[derive(Clone)]
-struct KeyValues<'a>(&'a dyn kv::Source);
+enum KeyValues<'a> {
+    Source(&'a dyn kv::Source),
+    #[cfg(feature = "std")]
+    Chained(std::rc::Rc<ChainedKeyValues<'a>>),
+}
+
+#[cfg(all(feature = "kv_unstable", feature = "std"))]
+struct ChainedKeyValues<'a> {
+    prev: KeyValues<'a>,
+    source: &'a dyn kv::Source,
+}
+
+#[cfg(feature = "kv_unstable")]
+impl<'a> KeyValues<'a> {
+    fn empty() -> Self {
+        KeyValues::Source(&Option::None::<(kv::Key, kv::Value)>)
+    }
+
+    #[cfg(feature = "std")]
+    fn chain(&mut self, source: &'a dyn kv::Source) {
+        let prev = mem::replace(self, KeyValues::empty());
+
+        *self = KeyValues::Chained(std::rc::Rc::new(ChainedKeyValues { prev, source }));
+    }
+
+    fn as_source(&self) -> &dyn kv::Source {
+        match *self {
+            KeyValues::Source(source) => source,
+            #[cfg(feature = "std")]
+            KeyValues::Chained(ref chained) => &**chained,
+        }
+    }
+}
+
+#[cfg(all(feature = "kv_unstable", feature = "std"))]
+impl<'a> kv::Source for ChainedKeyValues<'a> {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn kv::Visitor<'kvs>) -> Result<(), kv::Error> {
+        self.prev.as_source().visit(visitor)?;
+        self.source.visit(visitor)
+    }
+
+    fn get<'v>(&'v self, key: kv::Key) -> Option<kv::Value<'v>> {
+        self.prev
+            .as_source()
+            .get(key.clone())
+            .or_else(|| self.source.get(key))
+    }
+
+    fn count(&self) -> usize {
+        self.prev.as_source().count() + self.source.count()
+    }
+}
 
 #[cfg(feature = "kv_unstable")]
 impl<'a> fmt::Debug for KeyValues<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut visitor = f.debug_map();
-        self.0.visit(&mut visitor).map_err(|_| fmt::Error)?;
+        self.as_source()
+            .visit(&mut visitor)
+            .map_err(|_| fmt::Error)?;
         visitor.finish()
     }
 }
@@ -838,6 +1332,15 @@ impl<'a> Record<'a> {
         self.metadata.level()
     }
 
+    /// Whether the message is at least as severe as the given level.
+    ///
+    /// More severe levels compare as less than less severe ones, so an `Error`
+    /// record is at least `Warn`, but a `Debug` record isn't.
+    #[inline]
+    pub fn at_least(&self, level: Level) -> bool {
+        self.level() <= level
+    }
+
     /// The name of the target of the directive.
     #[inline]
     pub fn target(&self) -> &'a str {
@@ -880,11 +1383,38 @@ impl<'a> Record<'a> {
         self.line
     }
 
+    /// The time the message was originally logged at, as nanoseconds since the Unix epoch.
+    ///
+    /// `log` never captures a timestamp itself, so this is `None` unless one was
+    /// explicitly set on the builder, such as when replaying stored records.
+    #[inline]
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
+    /// The id of the span the message was logged in.
+    ///
+    /// Along with [`trace_id`](#method.trace_id), this can be used to correlate
+    /// records without depending on a tracing framework. It's `None` unless one
+    /// was explicitly set on the builder.
+    #[inline]
+    pub fn span_id(&self) -> Option<u64> {
+        self.span_id
+    }
+
+    /// The id of the trace the message was logged in.
+    ///
+    /// This is `None` unless one was explicitly set on the builder.
+    #[inline]
+    pub fn trace_id(&self) -> Option<u128> {
+        self.trace_id
+    }
+
     /// The structued key-value pairs associated with the message.
     #[cfg(feature = "kv_unstable")]
     #[inline]
     pub fn key_values(&self) -> &dyn kv::Source {
-        self.key_values.0
+        self.key_values.as_source()
     }
 
     /// Create a new [`RecordBuilder`](struct.RecordBuilder.html) based on this record.
@@ -901,53 +1431,399 @@ impl<'a> Record<'a> {
                 module_path: self.module_path,
                 file: self.file,
                 line: self.line,
+                timestamp: self.timestamp,
+                span_id: self.span_id,
+                trace_id: self.trace_id,
                 key_values: self.key_values.clone(),
             },
         }
     }
-}
 
-/// Builder for [`Record`](struct.Record.html).
-///
-/// Typically should only be used by log library creators or for testing and "shim loggers".
-/// The `RecordBuilder` can set the different parameters of `Record` object, and returns
-/// the created object when `build` is called.
-///
-/// # Examples
-///
-///
-/// ```edition2018
-/// use log::{Level, Record};
-///
-/// let record = Record::builder()
-///                 .args(format_args!("Error!"))
-///                 .level(Level::Error)
-///                 .target("myApp")
-///                 .file(Some("server.rs"))
-///                 .line(Some(144))
-///                 .module_path(Some("server"))
-///                 .build();
-/// ```
-///
-/// Alternatively, use [`MetadataBuilder`](struct.MetadataBuilder.html):
-///
-/// ```edition2018
-/// use log::{Record, Level, MetadataBuilder};
-///
-/// let error_metadata = MetadataBuilder::new()
-///                         .target("myApp")
-///                         .level(Level::Error)
-///                         .build();
-///
-/// let record = Record::builder()
-///                 .metadata(error_metadata)
-///                 .args(format_args!("Error!"))
-///                 .line(Some(433))
-///                 .file(Some("app.rs"))
-///                 .module_path(Some("server"))
-///                 .build();
-/// ```
-#[derive(Debug)]
+    /// Compare two records by what they contain rather than how they were built.
+    ///
+    /// Records are equal if their metadata, locations, timestamps, span and trace ids,
+    /// rendered messages, and key-value pairs are equal. Key-value pairs are compared by their rendered
+    /// keys and values, ignoring the order they appear in.
+    ///
+    /// This is useful for asserting a captured record matches an expected one in tests.
+    #[cfg(feature = "std")]
+    pub fn eq_semantic(&self, other: &Record) -> bool {
+        #[cfg(feature = "kv_unstable")]
+        fn key_values(record: &Record) -> Vec<(String, String)> {
+            struct Collect(Vec<(String, String)>);
+
+            impl<'kvs> kv::Visitor<'kvs> for Collect {
+                fn visit_pair(
+                    &mut self,
+                    key: kv::Key<'kvs>,
+                    value: kv::Value<'kvs>,
+                ) -> Result<(), kv::Error> {
+                    self.0.push((key.to_string(), value.to_string()));
+                    Ok(())
+                }
+            }
+
+            let mut collect = Collect(Vec::new());
+            let _ = record.key_values().visit(&mut collect);
+
+            collect.0.sort();
+            collect.0
+        }
+
+        #[cfg(feature = "kv_unstable")]
+        let key_values_eq = key_values(self) == key_values(other);
+        #[cfg(not(feature = "kv_unstable"))]
+        let key_values_eq = true;
+
+        key_values_eq
+            && self.metadata == other.metadata
+            && self.module_path() == other.module_path()
+            && self.file() == other.file()
+            && self.line == other.line
+            && self.timestamp == other.timestamp
+            && self.span_id == other.span_id
+            && self.trace_id == other.trace_id
+            && self.args.to_string() == other.args.to_string()
+    }
+
+    /// Render this record as a line of newline-delimited JSON.
+    ///
+    /// The line is a single JSON object with `level`, `target`, and `message` fields,
+    /// followed by the record's key-value pairs, and ends with exactly one `\n`.
+    /// Values are rendered with [`Value::to_json_scalar`], so newlines embedded in
+    /// strings are escaped.
+    ///
+    /// [`Value::to_json_scalar`]: kv/value/struct.Value.html#method.to_json_scalar
+    #[cfg(feature = "kv_unstable_std")]
+    pub fn to_ndjson_line(&self) -> String {
+        let message = self.args.to_string();
+
+        let mut line = self.to_json_object(&[
+            ("level", kv::Value::from(self.level().as_str())),
+            ("target", kv::Value::from(self.target())),
+            ("message", kv::Value::from(&*message)),
+        ]);
+        line.push('\n');
+        line
+    }
+
+    /// Render this record as a JSON object using [Elastic Common Schema] field names.
+    ///
+    /// The object has `log.level`, `message`, and `log.logger` fields, followed by the
+    /// record's key-value pairs. The level is lowercased, as ECS expects, and the
+    /// logger is the record's target. Values are rendered with [`Value::to_json_scalar`].
+    ///
+    /// [Elastic Common Schema]: https://www.elastic.co/guide/en/ecs/current/index.html
+    /// [`Value::to_json_scalar`]: kv/value/struct.Value.html#method.to_json_scalar
+    #[cfg(feature = "kv_unstable_std")]
+    pub fn to_ecs_json(&self) -> String {
+        let level = self.level().as_str().to_lowercase();
+        let message = self.args.to_string();
+
+        let fields = [
+            ("log.level", kv::Value::from(&*level)),
+            ("message", kv::Value::from(&*message)),
+            ("log.logger", kv::Value::from(self.target())),
+        ];
+
+        self.to_json_object(&fields)
+    }
+
+    /// Compute a fingerprint identifying the logical event this record represents.
+    ///
+    /// The fingerprint combines the level, target, module path, and line, along
+    /// with the message template if one was captured as a `message.template`
+    /// key-value pair by [`log_template!`]. The formatted message and other
+    /// key-value pairs aren't included, so records logged from the same call
+    /// site share a fingerprint regardless of the values interpolated into them.
+    /// This makes it useful for grouping records in alerting systems.
+    ///
+    /// The fingerprint is computed with the standard library's `DefaultHasher`,
+    /// so it's only stable within a single build of a program. It shouldn't be
+    /// persisted.
+    ///
+    /// [`log_template!`]: macro.log_template.html
+    #[cfg(feature = "std")]
+    pub fn fingerprint(&self) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        #[cfg(feature = "kv_unstable")]
+        fn hash_template<H: Hasher>(record: &Record, hasher: &mut H) {
+            record
+                .key_values()
+                .get(kv::Key::from_str("message.template"))
+                .map(|template| template.to_string())
+                .hash(hasher);
+        }
+
+        #[cfg(not(feature = "kv_unstable"))]
+        fn hash_template<H: Hasher>(_: &Record, _: &mut H) {}
+
+        let mut hasher = DefaultHasher::new();
+
+        self.level().hash(&mut hasher);
+        self.target().hash(&mut hasher);
+        self.module_path().hash(&mut hasher);
+        self.line().hash(&mut hasher);
+        hash_template(self, &mut hasher);
+
+        hasher.finish()
+    }
+
+    /// Render this record as a line of text.
+    ///
+    /// The line starts with the level, followed by whichever of the target,
+    /// module path, and line the given options include, then the message.
+    /// With the `kv_unstable` feature, the record's key-value pairs can follow
+    /// the message as `key=value`, joined by the options' separator.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// use log::{DisplayOptions, Level, Record};
+    ///
+    /// let record = Record::builder()
+    ///     .level(Level::Info)
+    ///     .target("myApp")
+    ///     .module_path(Some("server"))
+    ///     .line(Some(144))
+    ///     .args(format_args!("started"))
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     "INFO myApp server:144: started",
+    ///     record.display_with(DisplayOptions::new().module_path(true).line(true)).to_string(),
+    /// );
+    /// ```
+    pub fn display_with<'r>(&'r self, options: DisplayOptions) -> RecordDisplay<'r, 'a> {
+        RecordDisplay {
+            record: self,
+            options,
+        }
+    }
+
+    // Render the given fields followed by the record's key-value pairs as a JSON object
+    #[cfg(feature = "kv_unstable_std")]
+    fn to_json_object(&self, fields: &[(&str, kv::Value)]) -> String {
+        struct Fields<'a>(&'a mut String);
+
+        impl<'a, 'kvs> kv::Visitor<'kvs> for Fields<'a> {
+            fn visit_pair(
+                &mut self,
+                key: kv::Key<'kvs>,
+                value: kv::Value<'kvs>,
+            ) -> Result<(), kv::Error> {
+                // Anything past the opening `{` is a previous field
+                if self.0.len() > 1 {
+                    self.0.push(',');
+                }
+                self.0
+                    .push_str(&kv::Value::from(key.as_str()).to_json_scalar());
+                self.0.push(':');
+                self.0.push_str(&value.to_json_scalar());
+                Ok(())
+            }
+        }
+
+        let mut object = String::from("{");
+
+        for (key, value) in fields {
+            let _ = kv::Visitor::visit_pair(
+                &mut Fields(&mut object),
+                kv::Key::from_str(key),
+                value.clone(),
+            );
+        }
+        let _ = self.key_values().visit(&mut Fields(&mut object));
+
+        object.push('}');
+        object
+    }
+}
+
+/// Options for rendering a [`Record`](struct.Record.html) as text with
+/// [`Record::display_with`](struct.Record.html#method.display_with).
+///
+/// By default, the target and key-value pairs are included, the module path
+/// and line aren't, and key-value pairs are separated by a space.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayOptions {
+    target: bool,
+    module_path: bool,
+    line: bool,
+    #[cfg(feature = "kv_unstable")]
+    key_values: bool,
+    #[cfg(feature = "kv_unstable")]
+    separator: &'static str,
+}
+
+impl DisplayOptions {
+    /// Create the default options.
+    pub fn new() -> DisplayOptions {
+        DisplayOptions {
+            target: true,
+            module_path: false,
+            line: false,
+            #[cfg(feature = "kv_unstable")]
+            key_values: true,
+            #[cfg(feature = "kv_unstable")]
+            separator: " ",
+        }
+    }
+
+    /// Set whether to include the target.
+    pub fn target(mut self, target: bool) -> DisplayOptions {
+        self.target = target;
+        self
+    }
+
+    /// Set whether to include the module path, if the record has one.
+    pub fn module_path(mut self, module_path: bool) -> DisplayOptions {
+        self.module_path = module_path;
+        self
+    }
+
+    /// Set whether to include the line, if the record has one.
+    pub fn line(mut self, line: bool) -> DisplayOptions {
+        self.line = line;
+        self
+    }
+
+    /// Set whether to include the key-value pairs.
+    #[cfg(feature = "kv_unstable")]
+    pub fn key_values(mut self, key_values: bool) -> DisplayOptions {
+        self.key_values = key_values;
+        self
+    }
+
+    /// Set the separator to put between key-value pairs.
+    #[cfg(feature = "kv_unstable")]
+    pub fn separator(mut self, separator: &'static str) -> DisplayOptions {
+        self.separator = separator;
+        self
+    }
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        DisplayOptions::new()
+    }
+}
+
+/// The result of calling [`Record::display_with`](struct.Record.html#method.display_with).
+#[derive(Clone, Copy, Debug)]
+pub struct RecordDisplay<'r, 'a: 'r> {
+    record: &'r Record<'a>,
+    options: DisplayOptions,
+}
+
+impl<'r, 'a> fmt::Display for RecordDisplay<'r, 'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.record.level())?;
+
+        if self.options.target {
+            write!(f, " {}", self.record.target())?;
+        }
+
+        if let (true, Some(module_path)) = (self.options.module_path, self.record.module_path()) {
+            write!(f, " {}", module_path)?;
+        }
+
+        if let (true, Some(line)) = (self.options.line, self.record.line()) {
+            write!(f, ":{}", line)?;
+        }
+
+        write!(f, ": {}", self.record.args())?;
+
+        self.fmt_key_values(f)
+    }
+}
+
+impl<'r, 'a> RecordDisplay<'r, 'a> {
+    #[cfg(feature = "kv_unstable")]
+    fn fmt_key_values(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct Fields<'f, 'b: 'f> {
+            f: &'f mut fmt::Formatter<'b>,
+            separator: &'static str,
+            first: bool,
+        }
+
+        impl<'f, 'b, 'kvs> kv::Visitor<'kvs> for Fields<'f, 'b> {
+            fn visit_pair(
+                &mut self,
+                key: kv::Key<'kvs>,
+                value: kv::Value<'kvs>,
+            ) -> Result<(), kv::Error> {
+                let separator = if self.first { " " } else { self.separator };
+                self.first = false;
+
+                write!(self.f, "{}{}={}", separator, key, value)?;
+                Ok(())
+            }
+        }
+
+        if !self.options.key_values {
+            return Ok(());
+        }
+
+        self.record
+            .key_values()
+            .visit(&mut Fields {
+                f,
+                separator: self.options.separator,
+                first: true,
+            })
+            .map_err(|_| fmt::Error)
+    }
+
+    #[cfg(not(feature = "kv_unstable"))]
+    fn fmt_key_values(&self, _: &mut fmt::Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Builder for [`Record`](struct.Record.html).
+///
+/// Typically should only be used by log library creators or for testing and "shim loggers".
+/// The `RecordBuilder` can set the different parameters of `Record` object, and returns
+/// the created object when `build` is called.
+///
+/// # Examples
+///
+///
+/// ```edition2018
+/// use log::{Level, Record};
+///
+/// let record = Record::builder()
+///                 .args(format_args!("Error!"))
+///                 .level(Level::Error)
+///                 .target("myApp")
+///                 .file(Some("server.rs"))
+///                 .line(Some(144))
+///                 .module_path(Some("server"))
+///                 .build();
+/// ```
+///
+/// Alternatively, use [`MetadataBuilder`](struct.MetadataBuilder.html):
+///
+/// ```edition2018
+/// use log::{Record, Level, MetadataBuilder};
+///
+/// let error_metadata = MetadataBuilder::new()
+///                         .target("myApp")
+///                         .level(Level::Error)
+///                         .build();
+///
+/// let record = Record::builder()
+///                 .metadata(error_metadata)
+///                 .args(format_args!("Error!"))
+///                 .line(Some(433))
+///                 .file(Some("app.rs"))
+///                 .module_path(Some("server"))
+///                 .build();
+/// ```
+#[derive(Debug)]
 pub struct RecordBuilder<'a> {
     record: Record<'a>,
 }
@@ -962,6 +1838,9 @@ impl<'a> RecordBuilder<'a> {
     /// - `module_path`: `None`
     /// - `file`: `None`
     /// - `line`: `None`
+    /// - `timestamp`: `None`
+    /// - `span_id`: `None`
+    /// - `trace_id`: `None`
     ///
     /// [`format_args!("")`]: https://doc.rust-lang.org/std/macro.format_args.html
     /// [`Metadata::builder().build()`]: struct.MetadataBuilder.html#method.build
@@ -974,8 +1853,11 @@ impl<'a> RecordBuilder<'a> {
                 module_path: None,
                 file: None,
                 line: None,
+                timestamp: None,
+                span_id: None,
+                trace_id: None,
                 #[cfg(feature = "kv_unstable")]
-                key_values: KeyValues(&Option::None::<(kv::Key, kv::Value)>),
+                key_values: KeyValues::empty(),
             },
         }
     }
@@ -1043,11 +1925,48 @@ impl<'a> RecordBuilder<'a> {
         self
     }
 
+    /// Set [`timestamp`](struct.Record.html#method.timestamp)
+    #[inline]
+    pub fn timestamp(&mut self, timestamp: Option<u64>) -> &mut RecordBuilder<'a> {
+        self.record.timestamp = timestamp;
+        self
+    }
+
+    /// Set [`span_id`](struct.Record.html#method.span_id)
+    #[inline]
+    pub fn span_id(&mut self, span_id: Option<u64>) -> &mut RecordBuilder<'a> {
+        self.record.span_id = span_id;
+        self
+    }
+
+    /// Set [`trace_id`](struct.Record.html#method.trace_id)
+    #[inline]
+    pub fn trace_id(&mut self, trace_id: Option<u128>) -> &mut RecordBuilder<'a> {
+        self.record.trace_id = trace_id;
+        self
+    }
+
     /// Set [`key_values`](struct.Record.html#method.key_values)
     #[cfg(feature = "kv_unstable")]
     #[inline]
     pub fn key_values(&mut self, kvs: &'a dyn kv::Source) -> &mut RecordBuilder<'a> {
-        self.record.key_values = KeyValues(kvs);
+        self.record.key_values = KeyValues::Source(kvs);
+        self
+    }
+
+    /// Chain a source onto any previously set [`key_values`](struct.Record.html#method.key_values).
+    ///
+    /// The pairs of each source are visited in the order they were added.
+    /// Calling [`key_values`](#method.key_values) afterwards replaces the whole chain.
+    ///
+    /// Each source is linked onto the ones before it, so the chain isn't copied
+    /// when the record is built.
+    ///
+    /// Requires the `std` feature.
+    #[cfg(all(feature = "kv_unstable", feature = "std"))]
+    #[inline]
+    pub fn chain_key_values(&mut self, kvs: &'a dyn kv::Source) -> &mut RecordBuilder<'a> {
+        self.record.key_values.chain(kvs);
         self
     }
 
@@ -1109,6 +2028,34 @@ impl<'a> Metadata<'a> {
         MetadataBuilder::new()
     }
 
+    /// Returns metadata with the given level and target.
+    #[inline]
+    pub fn from_level_target(level: Level, target: &'a str) -> Metadata<'a> {
+        Metadata { level, target }
+    }
+
+    /// Parse a level and target from a string like `"INFO app"`.
+    ///
+    /// The level is parsed the same way as [`Level::from_str`], and the target is
+    /// the rest of the string after whitespace. Returns `None` if the level is invalid
+    /// or there is no target.
+    ///
+    /// [`Level::from_str`]: enum.Level.html#impl-FromStr
+    #[cfg(feature = "std")]
+    pub fn parse_level_target(s: &str) -> Option<(Level, String)> {
+        let s = s.trim();
+        let mut parts = s.splitn(2, char::is_whitespace);
+
+        let level = parts.next()?.parse().ok()?;
+        let target = parts.next()?.trim_start();
+
+        if target.is_empty() {
+            None
+        } else {
+            Some((level, target.to_owned()))
+        }
+    }
+
     /// The verbosity level of the message.
     #[inline]
     pub fn level(&self) -> Level {
@@ -1236,7 +2183,34 @@ where
 /// Generally, this should only be called by the active logging implementation.
 #[inline]
 pub fn set_max_level(level: LevelFilter) {
-    MAX_LOG_LEVEL_FILTER.store(level as usize, Ordering::SeqCst)
+    MAX_LOG_LEVEL_FILTER.store(level as usize, Ordering::SeqCst);
+    bump_max_level_generation();
+}
+
+#[cfg(atomic_cas)]
+fn bump_max_level_generation() {
+    MAX_LOG_LEVEL_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+// Platforms without atomic read-modify-write operations bump the generation
+// with a separate load and store. Racing calls may only bump it once, but it's
+// still guaranteed to change.
+#[cfg(not(atomic_cas))]
+fn bump_max_level_generation() {
+    let generation = MAX_LOG_LEVEL_GENERATION.load(Ordering::SeqCst);
+    MAX_LOG_LEVEL_GENERATION.store(generation.wrapping_add(1), Ordering::SeqCst);
+}
+
+/// Returns the number of times the maximum log level has been set.
+///
+/// Loggers that cache whether targets are enabled can compare this value
+/// against the one they saw when populating their cache to tell when
+/// [`set_max_level`] has been called since, and the cache needs to be rebuilt.
+///
+/// [`set_max_level`]: fn.set_max_level.html
+#[inline]
+pub fn max_level_generation() -> usize {
+    MAX_LOG_LEVEL_GENERATION.load(Ordering::SeqCst)
 }
 
 /// Returns the current maximum log level.
@@ -1263,6 +2237,27 @@ pub fn max_level() -> LevelFilter {
     unsafe { mem::transmute(MAX_LOG_LEVEL_FILTER.load(Ordering::Relaxed)) }
 }
 
+// Targets are usually module paths, so anything this long is likely to be a bug
+const MAX_TARGET_LEN: usize = 1024;
+
+/// Check whether a target is well formed.
+///
+/// A valid target has no newlines and is at most 1024 bytes long. Targets
+/// are usually module paths, so one that doesn't meet these rules has likely
+/// been set to some unrelated data by mistake, such as user input. Targets
+/// aren't checked when records are built, so this function can be used by
+/// loggers or callers that want to reject them.
+///
+/// # Examples
+///
+/// ```edition2018
+/// assert!(log::is_valid_target("my_app::db"));
+/// assert!(!log::is_valid_target("line one\nline two"));
+/// ```
+pub fn is_valid_target(target: &str) -> bool {
+    target.len() <= MAX_TARGET_LEN && !target.contains(&['\n', '\r'][..])
+}
+
 /// Sets the global logger to a `Box<Log>`.
 ///
 /// This is a simple convenience wrapper over `set_logger`, which takes a
@@ -1488,6 +2483,78 @@ pub fn __private_api_log_lit(
     );
 }
 
+// WARNING: this is not part of the crate's public API and is subject to change at any time
+#[cfg(feature = "kv_unstable")]
+#[doc(hidden)]
+pub fn __private_api_log_template(
+    args: fmt::Arguments,
+    level: Level,
+    &(target, module_path, file, line): &(&str, &'static str, &'static str, u32),
+    template: &'static str,
+) {
+    logger().log(
+        &Record::builder()
+            .args(args)
+            .level(level)
+            .target(target)
+            .module_path_static(Some(module_path))
+            .file_static(Some(file))
+            .line(Some(line))
+            .key_values(&("message.template", template))
+            .build(),
+    );
+}
+
+// WARNING: this is not part of the crate's public API and is subject to change at any time
+#[cfg(all(feature = "kv_unstable", feature = "std"))]
+#[doc(hidden)]
+pub fn __private_api_log_captured(
+    args: fmt::Arguments,
+    level: Level,
+    &(target, module_path, file, line): &(&str, &'static str, &'static str, u32),
+    captured: &[&dyn kv::ToValue],
+) {
+    logger().log(
+        &Record::builder()
+            .args(args)
+            .level(level)
+            .target(target)
+            .module_path_static(Some(module_path))
+            .file_static(Some(file))
+            .line(Some(line))
+            .key_values(&CapturedArgs(captured))
+            .build(),
+    );
+}
+
+// The positional arguments captured by `log_captured!`, as `arg.0`, `arg.1`, and so on
+#[cfg(all(feature = "kv_unstable", feature = "std"))]
+struct CapturedArgs<'a>(&'a [&'a dyn kv::ToValue]);
+
+#[cfg(all(feature = "kv_unstable", feature = "std"))]
+impl<'a> kv::Source for CapturedArgs<'a> {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn kv::Visitor<'kvs>) -> Result<(), kv::Error> {
+        for (index, arg) in self.0.iter().enumerate() {
+            visitor.visit_pair(
+                kv::Key::from_owned(format!("arg.{}", index)),
+                arg.to_value(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn get<'v>(&'v self, key: kv::Key) -> Option<kv::Value<'v>> {
+        let index: usize = key.as_str().strip_prefix("arg.")?.parse().ok()?;
+
+        self.0.get(index).map(|arg| arg.to_value())
+    }
+
+    fn count(&self) -> usize {
+        self.0.len()
+    }
+}
+
 // WARNING: this is not part of the crate's public API and is subject to change at any time
 #[doc(hidden)]
 pub fn __private_api_enabled(level: Level, target: &str) -> bool {
@@ -1608,6 +2675,113 @@ mod tests {
         assert_eq!("ERROR", LevelFilter::Error.to_string());
     }
 
+    #[test]
+    fn test_level_prefix_padded() {
+        for &level in &[
+            Level::Error,
+            Level::Warn,
+            Level::Info,
+            Level::Debug,
+            Level::Trace,
+        ] {
+            let prefix = level.prefix_padded();
+
+            assert_eq!(6, prefix.len());
+            assert!(prefix.ends_with(' '));
+            assert_eq!(level.as_str(), prefix.trim_end());
+        }
+    }
+
+    #[test]
+    fn test_level_syslog_severity() {
+        assert_eq!(3, Level::Error.to_syslog_severity());
+        assert_eq!(4, Level::Warn.to_syslog_severity());
+        assert_eq!(6, Level::Info.to_syslog_severity());
+        assert_eq!(7, Level::Debug.to_syslog_severity());
+        assert_eq!(7, Level::Trace.to_syslog_severity());
+
+        assert_eq!(Level::Error, Level::from_syslog_severity(0));
+        assert_eq!(Level::Error, Level::from_syslog_severity(3));
+        assert_eq!(Level::Warn, Level::from_syslog_severity(4));
+        assert_eq!(Level::Info, Level::from_syslog_severity(5));
+        assert_eq!(Level::Info, Level::from_syslog_severity(6));
+        assert_eq!(Level::Debug, Level::from_syslog_severity(7));
+        assert_eq!(Level::Debug, Level::from_syslog_severity(8));
+
+        assert_eq!(6, Level::Info.to_gelf_level());
+        assert_eq!(Level::Warn, Level::from_gelf_level(4));
+    }
+
+    #[test]
+    fn test_level_syslog_severity_round_trip() {
+        for &level in &[
+            Level::Error,
+            Level::Warn,
+            Level::Info,
+            Level::Debug,
+            Level::Trace,
+        ] {
+            let severity = level.to_syslog_severity();
+            let round_tripped = Level::from_syslog_severity(severity);
+
+            assert_eq!(severity, round_tripped.to_syslog_severity());
+
+            // `Trace` shares its severity with `Debug`
+            if level != Level::Trace {
+                assert_eq!(level, round_tripped);
+            }
+        }
+    }
+
+    #[test]
+    fn test_level_debug() {
+        assert_eq!("Error", format!("{:?}", Level::Error));
+        assert_eq!("error", format!("{:#?}", Level::Error));
+        assert_eq!("Off", format!("{:?}", LevelFilter::Off));
+        assert_eq!("trace", format!("{:#?}", LevelFilter::Trace));
+    }
+
+    #[test]
+    fn test_max_level_generation() {
+        use super::{max_level, max_level_generation, set_max_level};
+
+        let generation = max_level_generation();
+        set_max_level(max_level());
+        assert_ne!(generation, max_level_generation());
+    }
+
+    #[test]
+    #[cfg(atomic_cas)]
+    fn test_max_level_generation_concurrent() {
+        use super::{max_level, max_level_generation, set_max_level};
+        use std::thread;
+
+        let generation = max_level_generation();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    for _ in 0..1000 {
+                        set_max_level(max_level());
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Other tests may set the max level too, so this is a lower bound
+        assert!(max_level_generation().wrapping_sub(generation) >= 4000);
+    }
+
+    #[test]
+    fn test_levelfilter_show_padded() {
+        assert_eq!(" WARN", format!("{:>5}", LevelFilter::Warn));
+        assert_eq!("OFF  ", format!("{:<5}", LevelFilter::Off));
+        assert_eq!("*INFO*", format!("{:*^6}", LevelFilter::Info));
+    }
+
     #[test]
     fn test_cross_cmp() {
         assert!(Level::Debug > LevelFilter::Error);
@@ -1635,6 +2809,49 @@ mod tests {
         assert_eq!(LevelFilter::Trace, Level::Trace.to_level_filter());
     }
 
+    #[test]
+    fn test_level_into_u8() {
+        assert_eq!(1, u8::from(Level::Error));
+        assert_eq!(3, u8::from(Level::Info));
+        assert_eq!(5, u8::from(Level::Trace));
+    }
+
+    #[test]
+    fn test_level_filter_more_verbose() {
+        assert_eq!(LevelFilter::Error, LevelFilter::Off.more_verbose());
+        assert_eq!(LevelFilter::Debug, LevelFilter::Info.more_verbose());
+        assert_eq!(LevelFilter::Trace, LevelFilter::Trace.more_verbose());
+    }
+
+    #[test]
+    fn test_level_filter_less_verbose() {
+        assert_eq!(LevelFilter::Debug, LevelFilter::Trace.less_verbose());
+        assert_eq!(LevelFilter::Off, LevelFilter::Error.less_verbose());
+        assert_eq!(LevelFilter::Off, LevelFilter::Off.less_verbose());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_level_filter_from_env() {
+        std::env::set_var("LOG_TEST_LEVEL_FILTER_NAME", "debug");
+        std::env::set_var("LOG_TEST_LEVEL_FILTER_NUMBER", "1");
+        std::env::set_var("LOG_TEST_LEVEL_FILTER_INVALID", "loud");
+
+        let from_env = |var| LevelFilter::from_env(var, LevelFilter::Warn);
+
+        assert_eq!(LevelFilter::Debug, from_env("LOG_TEST_LEVEL_FILTER_NAME"));
+        assert_eq!(LevelFilter::Error, from_env("LOG_TEST_LEVEL_FILTER_NUMBER"));
+        assert_eq!(LevelFilter::Warn, from_env("LOG_TEST_LEVEL_FILTER_INVALID"));
+        assert_eq!(LevelFilter::Warn, from_env("LOG_TEST_LEVEL_FILTER_UNSET"));
+    }
+
+    #[test]
+    fn test_level_filter_into_u8() {
+        assert_eq!(0, u8::from(LevelFilter::Off));
+        assert_eq!(3, u8::from(LevelFilter::Info));
+        assert_eq!(5, u8::from(LevelFilter::Trace));
+    }
+
     #[test]
     fn test_level_filter_as_str() {
         let tests = &[
@@ -1650,6 +2867,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_level_set_from_filter() {
+        use super::LevelSet;
+
+        assert!(LevelSet::from_filter(LevelFilter::Off).is_empty());
+
+        let set = LevelSet::from_filter(LevelFilter::Info);
+        assert!(set.contains(Level::Error));
+        assert!(set.contains(Level::Warn));
+        assert!(set.contains(Level::Info));
+        assert!(!set.contains(Level::Debug));
+        assert!(!set.contains(Level::Trace));
+
+        let levels: std::vec::Vec<Level> =
+            LevelSet::from_filter(LevelFilter::Trace).iter().collect();
+        assert_eq!(
+            levels,
+            [
+                Level::Error,
+                Level::Warn,
+                Level::Info,
+                Level::Debug,
+                Level::Trace
+            ]
+        );
+    }
+
+    #[test]
+    fn test_level_set_non_contiguous() {
+        use super::LevelSet;
+
+        let mut set = LevelSet::empty();
+        set.insert(Level::Error);
+        set.insert(Level::Trace);
+
+        assert!(set.contains(Level::Error));
+        assert!(!set.contains(Level::Warn));
+        assert!(!set.contains(Level::Info));
+        assert!(!set.contains(Level::Debug));
+        assert!(set.contains(Level::Trace));
+
+        let levels: std::vec::Vec<Level> = set.iter().collect();
+        assert_eq!(levels, [Level::Error, Level::Trace]);
+    }
+
     #[test]
     #[cfg(feature = "std")]
     fn test_error_trait() {
@@ -1662,6 +2924,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_valid_target() {
+        use super::{is_valid_target, Record};
+
+        assert!(is_valid_target("my_app::db"));
+        assert!(is_valid_target(""));
+
+        assert!(!is_valid_target("line one\nline two"));
+        assert!(!is_valid_target("line one\r\nline two"));
+        assert!(!is_valid_target(&"a".repeat(1025)));
+
+        let record = Record::builder().target("line one\nline two").build();
+        assert_eq!("line one\nline two", record.target());
+    }
+
     #[test]
     fn test_metadata_builder() {
         use super::MetadataBuilder;
@@ -1674,6 +2951,36 @@ mod tests {
         assert_eq!(metadata_test.target(), "myApp");
     }
 
+    #[test]
+    fn test_metadata_from_level_target() {
+        use super::Metadata;
+        let metadata_test = Metadata::from_level_target(Level::Warn, "myApp");
+        assert_eq!(metadata_test.level(), Level::Warn);
+        assert_eq!(metadata_test.target(), "myApp");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_metadata_parse_level_target() {
+        use super::Metadata;
+        let tests = [
+            ("INFO app", Some((Level::Info, "app"))),
+            ("warn  app::module", Some((Level::Warn, "app::module"))),
+            (" ERROR\tapp ", Some((Level::Error, "app"))),
+            ("INFO", None),
+            ("INFO ", None),
+            ("LOUD app", None),
+            ("", None),
+        ];
+
+        for &(input, expected) in &tests {
+            assert_eq!(
+                expected.map(|(level, target)| (level, target.to_owned())),
+                Metadata::parse_level_target(input)
+            );
+        }
+    }
+
     #[test]
     fn test_metadata_convenience_builder() {
         use super::Metadata;
@@ -1742,6 +3049,154 @@ mod tests {
         assert_eq!(record_test.line(), Some(30));
     }
 
+    #[test]
+    fn test_record_timestamp_builder() {
+        use super::Record;
+        assert_eq!(Record::builder().build().timestamp(), None);
+
+        let record_test = Record::builder()
+            .timestamp(Some(1_600_000_000_000_000_000))
+            .build();
+        assert_eq!(record_test.timestamp(), Some(1_600_000_000_000_000_000));
+    }
+
+    #[test]
+    fn test_record_at_least() {
+        use super::Record;
+        let record_test = Record::builder().level(Level::Error).build();
+        assert!(record_test.at_least(Level::Warn));
+        assert!(record_test.at_least(Level::Error));
+
+        let record_test = Record::builder().level(Level::Debug).build();
+        assert!(!record_test.at_least(Level::Warn));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_record_fingerprint() {
+        use super::Record;
+
+        fn fingerprint(name: &str, line: u32) -> u64 {
+            Record::builder()
+                .level(Level::Error)
+                .target("myApp")
+                .module_path(Some("server"))
+                .line(Some(line))
+                .args(format_args!("user {} not found", name))
+                .build()
+                .fingerprint()
+        }
+
+        assert_eq!(fingerprint("alice", 42), fingerprint("bob", 42));
+        assert_ne!(fingerprint("alice", 42), fingerprint("alice", 43));
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "kv_unstable"))]
+    fn test_record_fingerprint_template() {
+        use super::Record;
+
+        fn fingerprint(template: &str, user: &str) -> u64 {
+            let key_values: &[_] = &[("message.template", template), ("user", user)];
+
+            Record::builder()
+                .level(Level::Error)
+                .target("myApp")
+                .line(Some(42))
+                .key_values(&key_values)
+                .build()
+                .fingerprint()
+        }
+
+        assert_eq!(
+            fingerprint("user {} not found", "alice"),
+            fingerprint("user {} not found", "bob")
+        );
+        assert_ne!(
+            fingerprint("user {} not found", "alice"),
+            fingerprint("user {} is locked", "alice")
+        );
+    }
+
+    #[test]
+    fn test_record_display_with() {
+        use super::{DisplayOptions, Record};
+
+        let record = Record::builder()
+            .level(Level::Warn)
+            .target("myApp")
+            .module_path(Some("foo"))
+            .line(Some(30))
+            .args(format_args!("hello"))
+            .build();
+
+        assert_eq!(
+            "WARN myApp: hello",
+            record.display_with(DisplayOptions::new()).to_string()
+        );
+        assert_eq!(
+            "WARN: hello",
+            record
+                .display_with(DisplayOptions::new().target(false))
+                .to_string()
+        );
+        assert_eq!(
+            "WARN myApp foo:30: hello",
+            record
+                .display_with(DisplayOptions::new().module_path(true).line(true))
+                .to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable")]
+    fn test_record_display_with_key_values() {
+        use super::{DisplayOptions, Record};
+
+        let kvs: &[(&str, i32)] = &[("a", 1), ("b", 2)];
+        let record = Record::builder()
+            .level(Level::Info)
+            .target("myApp")
+            .args(format_args!("hello"))
+            .key_values(&kvs)
+            .build();
+
+        assert_eq!(
+            "INFO myApp: hello a=1 b=2",
+            record.display_with(DisplayOptions::new()).to_string()
+        );
+        assert_eq!(
+            "INFO myApp: hello a=1, b=2",
+            record
+                .display_with(DisplayOptions::new().separator(", "))
+                .to_string()
+        );
+        assert_eq!(
+            "INFO myApp: hello",
+            record
+                .display_with(DisplayOptions::new().key_values(false))
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_record_span_trace_id_builder() {
+        use super::Record;
+        let record_test = Record::builder().build();
+        assert_eq!(record_test.span_id(), None);
+        assert_eq!(record_test.trace_id(), None);
+
+        let record_test = Record::builder()
+            .span_id(Some(42))
+            .trace_id(Some(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736))
+            .build();
+        assert_eq!(record_test.span_id(), Some(42));
+        assert_eq!(
+            record_test.trace_id(),
+            Some(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736)
+        );
+    }
+
     #[test]
     #[cfg(feature = "kv_unstable")]
     fn test_record_key_values_builder() {
@@ -1773,6 +3228,100 @@ mod tests {
         assert_eq!(2, visitor.seen_pairs);
     }
 
+    #[test]
+    #[cfg(all(feature = "kv_unstable", feature = "std"))]
+    fn test_record_chain_key_values_builder() {
+        use super::Record;
+        use kv::source::as_map;
+
+        let fields: &[(&str, i32)] = &[("a", 1), ("b", 2)];
+        let context = ("c", 3);
+        let record_test = Record::builder()
+            .chain_key_values(&fields)
+            .chain_key_values(&context)
+            .build();
+
+        assert_eq!(3, record_test.key_values().count());
+        assert_eq!(
+            r#"{"a": 1, "b": 2, "c": 3}"#,
+            format!("{:?}", as_map(record_test.key_values()))
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "kv_unstable", feature = "std"))]
+    fn test_record_chain_key_values_builder_many() {
+        use super::Record;
+        use kv::source::as_map;
+        use kv::Key;
+
+        let sources = [("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5), ("f", 6)];
+
+        let mut builder = Record::builder();
+        builder.key_values(&("z", 0));
+        for source in &sources {
+            builder.chain_key_values(source);
+        }
+        let record_test = builder.build();
+
+        assert_eq!(7, record_test.key_values().count());
+        assert_eq!(
+            r#"{"z": 0, "a": 1, "b": 2, "c": 3, "d": 4, "e": 5, "f": 6}"#,
+            format!("{:?}", as_map(record_test.key_values()))
+        );
+        assert_eq!(
+            Some(6),
+            record_test
+                .key_values()
+                .get(Key::from_str("f"))
+                .and_then(|value| value.to_i64())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable_std")]
+    fn test_record_to_ndjson_line() {
+        use super::Record;
+
+        let kvs: &[(&str, &str)] = &[("user", "first\nsecond")];
+        let line = Record::builder()
+            .level(Level::Warn)
+            .target("myApp")
+            .args(format_args!("hello \"{}\"", "world"))
+            .key_values(&kvs)
+            .build()
+            .to_ndjson_line();
+
+        assert_eq!(
+            concat!(
+                r#"{"level":"WARN","target":"myApp","message":"hello \"world\"","user":"first\nsecond"}"#,
+                "\n"
+            ),
+            line
+        );
+        assert_eq!(1, line.matches('\n').count());
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable_std")]
+    fn test_record_to_ecs_json() {
+        use super::Record;
+
+        let kvs: &[(&str, i32)] = &[("user.id", 42)];
+        let json = Record::builder()
+            .level(Level::Warn)
+            .target("myApp")
+            .args(format_args!("hello"))
+            .key_values(&kvs)
+            .build()
+            .to_ecs_json();
+
+        assert_eq!(
+            r#"{"log.level":"warn","message":"hello","log.logger":"myApp","user.id":42}"#,
+            json
+        );
+    }
+
     #[test]
     #[cfg(feature = "kv_unstable")]
     fn test_record_key_values_get_coerce() {
@@ -1791,4 +3340,50 @@ mod tests {
                 .expect("invalid value")
         );
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_record_eq_semantic() {
+        use super::{Level, Record};
+
+        let expected = Record::builder()
+            .args(format_args!("hello world"))
+            .line(Some(30))
+            .target("myApp")
+            .level(Level::Warn)
+            .build();
+
+        assert!(Record::builder()
+            .level(Level::Warn)
+            .target("myApp")
+            .args(format_args!("hello {}", "world"))
+            .line(Some(30))
+            .build()
+            .eq_semantic(&expected));
+
+        assert!(!Record::builder()
+            .level(Level::Warn)
+            .target("myApp")
+            .args(format_args!("hello world"))
+            .line(Some(31))
+            .build()
+            .eq_semantic(&expected));
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "kv_unstable"))]
+    fn test_record_eq_semantic_key_values() {
+        use super::Record;
+
+        let a: &[(&str, i32)] = &[("a", 1), ("b", 2)];
+        let b: &[(&str, i32)] = &[("b", 2), ("a", 1)];
+        let c: &[(&str, i32)] = &[("b", 2), ("a", 3)];
+
+        let a = Record::builder().key_values(&a).build();
+        let b = Record::builder().key_values(&b).build();
+        let c = Record::builder().key_values(&c).build();
+
+        assert!(a.eq_semantic(&b));
+        assert!(!a.eq_semantic(&c));
+    }
 }
@@ -0,0 +1,130 @@
+//! Combinators for wrapping loggers in adapters.
+
+use std::time::Duration;
+
+#[cfg(feature = "kv_unstable")]
+use kv::context::ContextLogger;
+#[cfg(feature = "kv_unstable")]
+use kv::Source;
+#[cfg(feature = "kv_unstable")]
+use WithFields;
+use {
+    Coalesce, FanOut, Filtered, FlushOnDrop, Log, Metadata, PanicOnError, RateLimited, Sampler,
+    TimestampLogger,
+};
+
+/// Methods for wrapping a logger in the adapters provided by this crate.
+///
+/// This trait is implemented for every [`Log`](trait.Log.html), so adapters
+/// can be stacked fluently instead of by nesting their constructors. Each
+/// method is the same as calling the adapter's `new` function with `self`.
+///
+/// Requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use log::{Level, Log, LogExt, Record};
+/// # struct MyLogger;
+/// # impl log::Log for MyLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, _: &log::Record) {}
+/// #     fn flush(&self) {}
+/// # }
+///
+/// let logger = MyLogger
+///     .rate_limited(100, Duration::from_secs(1))
+///     .filtered(|metadata| metadata.target() != "noisy");
+///
+/// logger.log(&Record::builder()
+///     .level(Level::Info)
+///     .args(format_args!("hello"))
+///     .build());
+/// logger.flush();
+/// ```
+pub trait LogExt: Log + Sized {
+    /// Add a fixed set of key-value pairs to each record.
+    ///
+    /// See [`WithFields`](struct.WithFields.html). Requires the `kv_unstable` feature.
+    #[cfg(feature = "kv_unstable")]
+    fn with_fields<S>(self, fields: S) -> WithFields<Self, S>
+    where
+        S: Source,
+    {
+        WithFields::new(self, fields)
+    }
+
+    /// Add the current thread's context to each record.
+    ///
+    /// See [`ContextLogger`](kv/context/struct.ContextLogger.html). Requires the
+    /// `kv_unstable` feature.
+    #[cfg(feature = "kv_unstable")]
+    fn contextual(self) -> ContextLogger<Self> {
+        ContextLogger::new(self)
+    }
+
+    /// Forward at most `limit` records every `period`.
+    ///
+    /// See [`RateLimited`](struct.RateLimited.html).
+    fn rate_limited(self, limit: u32, period: Duration) -> RateLimited<Self> {
+        RateLimited::new(self, limit, period)
+    }
+
+    /// Only forward records whose metadata matches a predicate.
+    ///
+    /// See [`Filtered`](struct.Filtered.html).
+    fn filtered<F>(self, filter: F) -> Filtered<Self, F>
+    where
+        F: Fn(&Metadata) -> bool,
+    {
+        Filtered::new(self, filter)
+    }
+
+    /// Forward records to this logger and another one.
+    ///
+    /// See [`FanOut`](struct.FanOut.html).
+    fn fan_out<L>(self, other: L) -> FanOut<Self, L>
+    where
+        L: Log,
+    {
+        FanOut::new(self, other)
+    }
+
+    /// Flush this logger when it's dropped.
+    ///
+    /// See [`FlushOnDrop`](struct.FlushOnDrop.html).
+    fn flush_on_drop(self) -> FlushOnDrop<Self> {
+        FlushOnDrop::new(self)
+    }
+
+    /// Only forward a random sample of records.
+    ///
+    /// See [`Sampler`](struct.Sampler.html).
+    fn sampled(self, rate: f64) -> Sampler<Self> {
+        Sampler::new(self, rate)
+    }
+
+    /// Collapse runs of identical records.
+    ///
+    /// See [`Coalesce`](struct.Coalesce.html).
+    fn coalesced(self) -> Coalesce<Self> {
+        Coalesce::new(self)
+    }
+
+    /// Set the timestamp of records using the system time.
+    ///
+    /// See [`TimestampLogger`](struct.TimestampLogger.html).
+    fn timestamped(self) -> TimestampLogger<Self> {
+        TimestampLogger::new(self)
+    }
+
+    /// Panic when an `Error` record is logged.
+    ///
+    /// See [`PanicOnError`](struct.PanicOnError.html).
+    fn panic_on_error(self) -> PanicOnError<Self> {
+        PanicOnError::new(self)
+    }
+}
+
+impl<L> LogExt for L where L: Log {}
@@ -54,6 +54,161 @@ macro_rules! log {
     ($lvl:expr, $($arg:tt)+) => (log!(target: __log_module_path!(), $lvl, $($arg)+))
 }
 
+/// Logs a message, returning whether it was passed to the logger.
+///
+/// This macro accepts the same arguments as [`log!`](macro.log.html), but evaluates
+/// to `true` if the level was enabled and the record was passed to the logger,
+/// or `false` if it was filtered out. It's a shorthand for pairing
+/// [`log_enabled!`](macro.log_enabled.html) with `log!` when the caller needs to know
+/// whether a message actually went out, like to avoid reporting the same problem twice.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use log::{try_log, Level};
+///
+/// # fn main() {
+/// # fn report(_: &str) {}
+/// let err = "connection reset";
+///
+/// if !try_log!(Level::Warn, "request failed: {}", err) {
+///     report(err);
+/// }
+/// # }
+/// ```
+#[macro_export(local_inner_macros)]
+macro_rules! try_log {
+    (target: $target:expr, $lvl:expr, $message:expr) => ({
+        let lvl = $lvl;
+        if lvl <= $crate::STATIC_MAX_LEVEL && lvl <= $crate::max_level() {
+            // ensure that $message is a valid format string literal
+            let _ = __log_format_args!($message);
+            $crate::__private_api_log_lit(
+                $message,
+                lvl,
+                &($target, __log_module_path!(), __log_file!(), __log_line!()),
+            );
+            true
+        } else {
+            false
+        }
+    });
+    (target: $target:expr, $lvl:expr, $($arg:tt)+) => ({
+        let lvl = $lvl;
+        if lvl <= $crate::STATIC_MAX_LEVEL && lvl <= $crate::max_level() {
+            $crate::__private_api_log(
+                __log_format_args!($($arg)+),
+                lvl,
+                &($target, __log_module_path!(), __log_file!(), __log_line!()),
+            );
+            true
+        } else {
+            false
+        }
+    });
+    ($lvl:expr, $($arg:tt)+) => (try_log!(target: __log_module_path!(), $lvl, $($arg)+))
+}
+
+/// Logs a message, capturing each of its format arguments as a key-value pair.
+///
+/// This macro accepts the same arguments as [`log!`](macro.log.html), except that
+/// format arguments must be positional. As well as formatting the message, each
+/// argument is attached to the record as a key-value pair with the key `arg.0`,
+/// `arg.1`, and so on, in the order they're given. That means every argument must
+/// implement [`ToValue`](kv/trait.ToValue.html). Each argument is only evaluated
+/// once, and only if the level is enabled.
+///
+/// Requires the `kv_unstable` and `std` features.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use log::{log_captured, Level};
+///
+/// # fn main() {
+/// let user = "alice";
+/// let attempts = 3;
+///
+/// // Logs `login failed for alice after 3 attempts` with `arg.0=alice arg.1=3`
+/// log_captured!(Level::Warn, "login failed for {} after {} attempts", user, attempts);
+/// # }
+/// ```
+#[cfg(all(feature = "kv_unstable", feature = "std"))]
+#[macro_export(local_inner_macros)]
+macro_rules! log_captured {
+    (target: $target:expr, $lvl:expr, $message:expr $(, $arg:expr)* $(,)*) => ({
+        let lvl = $lvl;
+        if lvl <= $crate::STATIC_MAX_LEVEL && lvl <= $crate::max_level() {
+            __log_captured!(lvl, $target, $message, [] $(, $arg)*)
+        }
+    });
+    ($lvl:expr, $message:expr $(, $arg:expr)* $(,)*) => (
+        log_captured!(target: __log_module_path!(), $lvl, $message $(, $arg)*)
+    );
+}
+
+/// Logs a message, capturing its format string as a key-value pair.
+///
+/// This macro accepts the same arguments as [`log!`](macro.log.html). As well as
+/// formatting the message, the format string itself is attached to the record
+/// with the key `message.template`. So `"user {} logged in"` is kept alongside
+/// the rendered message, which lets backends group records by their template.
+///
+/// Requires the `kv_unstable` feature.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use log::{log_template, Level};
+///
+/// # fn main() {
+/// let user = "alice";
+///
+/// // Logs `user alice logged in` with `message.template="user {} logged in"`
+/// log_template!(Level::Info, "user {} logged in", user);
+/// # }
+/// ```
+#[cfg(feature = "kv_unstable")]
+#[macro_export(local_inner_macros)]
+macro_rules! log_template {
+    (target: $target:expr, $lvl:expr, $message:expr) => (
+        log_template!(target: $target, $lvl, $message,)
+    );
+    (target: $target:expr, $lvl:expr, $message:expr, $($arg:tt)*) => ({
+        let lvl = $lvl;
+        if lvl <= $crate::STATIC_MAX_LEVEL && lvl <= $crate::max_level() {
+            $crate::__private_api_log_template(
+                __log_format_args!($message, $($arg)*),
+                lvl,
+                &($target, __log_module_path!(), __log_file!(), __log_line!()),
+                $message,
+            );
+        }
+    });
+    ($lvl:expr, $($arg:tt)+) => (log_template!(target: __log_module_path!(), $lvl, $($arg)+))
+}
+
+// Each argument is bound to a local called `arg` before recursing. Every
+// expansion gets its own hygiene context, so they don't shadow each other,
+// and they're collected so the message and key-value pairs can share them.
+#[cfg(all(feature = "kv_unstable", feature = "std"))]
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! __log_captured {
+    ($lvl:expr, $target:expr, $message:expr, [$($bound:ident)*]) => (
+        $crate::__private_api_log_captured(
+            __log_format_args!($message, $($bound),*),
+            $lvl,
+            &($target, __log_module_path!(), __log_file!(), __log_line!()),
+            &[$($bound as &dyn $crate::kv::ToValue),*],
+        )
+    );
+    ($lvl:expr, $target:expr, $message:expr, [$($bound:ident)*], $arg:expr $(, $rest:expr)*) => ({
+        let arg = &$arg;
+        __log_captured!($lvl, $target, $message, [$($bound)* arg] $(, $rest)*)
+    });
+}
+
 /// Logs a message at the error level.
 ///
 /// # Examples
@@ -0,0 +1,369 @@
+//! Owned copies of log records.
+
+#[cfg(feature = "kv_unstable")]
+use kv;
+use {Level, Record};
+
+/// An owned copy of a [`Record`](struct.Record.html).
+///
+/// A `Record` borrows its message and metadata from the log call that created it,
+/// so it can't be stored or sent to another thread. An `OwnedRecord` copies them instead.
+/// The message is rendered into a string, and with the `kv_unstable` feature, the
+/// values of its key-value pairs are captured as owned values that keep their type.
+///
+/// Requires the `std` feature.
+#[derive(Clone, Debug)]
+pub struct OwnedRecord {
+    level: Level,
+    target: String,
+    message: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    timestamp: Option<u64>,
+    span_id: Option<u64>,
+    trace_id: Option<u128>,
+    #[cfg(feature = "kv_unstable")]
+    key_values: OwnedKeyValues,
+}
+
+impl OwnedRecord {
+    /// Copy a borrowed record.
+    pub fn from_record(record: &Record) -> OwnedRecord {
+        OwnedRecord {
+            level: record.level(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+            module_path: record.module_path().map(ToOwned::to_owned),
+            file: record.file().map(ToOwned::to_owned),
+            line: record.line(),
+            timestamp: record.timestamp(),
+            span_id: record.span_id(),
+            trace_id: record.trace_id(),
+            #[cfg(feature = "kv_unstable")]
+            key_values: OwnedKeyValues::from_source(record.key_values()),
+        }
+    }
+
+    /// Replace the message of this record.
+    ///
+    /// A `Record` can only carry its message as `fmt::Arguments`, which can't own
+    /// a `String`. Bridges that receive owned messages from other logging systems
+    /// can build an `OwnedRecord` with the message instead, and forward it with
+    /// [`with_record`](#method.with_record):
+    ///
+    /// ```
+    /// # use log::{Level, Log, OwnedRecord, Record};
+    /// fn forward(level: Level, message: String, logger: &dyn Log) {
+    ///     let record = OwnedRecord::from_record(&Record::builder().level(level).build())
+    ///         .with_message(message);
+    ///
+    ///     record.with_record(|record| logger.log(record));
+    /// }
+    /// ```
+    pub fn with_message(mut self, message: String) -> OwnedRecord {
+        self.message = message;
+        self
+    }
+
+    /// The verbosity level of the message.
+    #[inline]
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The name of the target of the directive.
+    #[inline]
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The rendered message body.
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The module path of the message.
+    #[inline]
+    pub fn module_path(&self) -> Option<&str> {
+        self.module_path.as_ref().map(|s| &s[..])
+    }
+
+    /// The source file containing the message.
+    #[inline]
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_ref().map(|s| &s[..])
+    }
+
+    /// The line containing the message.
+    #[inline]
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The time the message was originally logged at, as nanoseconds since the Unix epoch.
+    #[inline]
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
+    /// The id of the span the message was logged in.
+    #[inline]
+    pub fn span_id(&self) -> Option<u64> {
+        self.span_id
+    }
+
+    /// The id of the trace the message was logged in.
+    #[inline]
+    pub fn trace_id(&self) -> Option<u128> {
+        self.trace_id
+    }
+
+    /// The structured key-value pairs associated with the message.
+    #[cfg(feature = "kv_unstable")]
+    #[inline]
+    pub fn key_values(&self) -> &dyn kv::Source {
+        &self.key_values
+    }
+
+    /// Whether the key-value pairs of this record are the same as the given record's.
+    ///
+    /// Pairs are compared in order, by their keys and owned values.
+    pub(crate) fn key_values_eq(&self, record: &Record) -> bool {
+        #[cfg(feature = "kv_unstable")]
+        {
+            self.key_values.eq_source(record.key_values())
+        }
+        #[cfg(not(feature = "kv_unstable"))]
+        {
+            let _ = record;
+            true
+        }
+    }
+
+    /// Borrow this record as a [`Record`](struct.Record.html).
+    ///
+    /// A `Record` can't outlive the message it formats, so it's passed to the
+    /// given function instead of being returned. This can be used to forward
+    /// an owned record to a logger:
+    ///
+    /// ```
+    /// # use log::{Log, OwnedRecord};
+    /// fn forward(record: &OwnedRecord, logger: &dyn Log) {
+    ///     record.with_record(|record| logger.log(record));
+    /// }
+    /// ```
+    pub fn with_record<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Record) -> R,
+    {
+        let mut builder = Record::builder();
+
+        builder
+            .level(self.level)
+            .target(&self.target)
+            .module_path(self.module_path())
+            .file(self.file())
+            .line(self.line)
+            .timestamp(self.timestamp)
+            .span_id(self.span_id)
+            .trace_id(self.trace_id);
+
+        #[cfg(feature = "kv_unstable")]
+        builder.key_values(&self.key_values);
+
+        f(&builder.args(format_args!("{}", self.message)).build())
+    }
+}
+
+impl<'a> From<&'a Record<'a>> for OwnedRecord {
+    fn from(record: &'a Record<'a>) -> Self {
+        OwnedRecord::from_record(record)
+    }
+}
+
+#[cfg(feature = "kv_unstable")]
+#[derive(Clone, Debug)]
+struct OwnedKeyValues {
+    pairs: Vec<(String, kv::value::OwnedValue)>,
+    // The value the original source returned from `get` for each repeated key
+    repeated: Vec<(String, Option<kv::value::OwnedValue>)>,
+}
+
+#[cfg(feature = "kv_unstable")]
+impl OwnedKeyValues {
+    fn from_source(source: &dyn kv::Source) -> Self {
+        struct Collect(Vec<(String, kv::value::OwnedValue)>);
+
+        impl<'kvs> kv::Visitor<'kvs> for Collect {
+            fn visit_pair(
+                &mut self,
+                key: kv::Key<'kvs>,
+                value: kv::Value<'kvs>,
+            ) -> Result<(), kv::Error> {
+                self.0
+                    .push((key.as_str().to_owned(), value.to_owned_value()));
+                Ok(())
+            }
+        }
+
+        let mut collect = Collect(Vec::new());
+        let _ = source.visit(&mut collect);
+        let pairs = collect.0;
+
+        let mut repeated: Vec<(String, Option<kv::value::OwnedValue>)> = Vec::new();
+        for (i, (key, _)) in pairs.iter().enumerate() {
+            let is_repeated = pairs[..i].iter().any(|(k, _)| k == key);
+
+            if is_repeated && !repeated.iter().any(|(k, _)| k == key) {
+                let value = source
+                    .get(kv::Key::from_str(key))
+                    .map(|value| value.to_owned_value());
+
+                repeated.push((key.clone(), value));
+            }
+        }
+
+        OwnedKeyValues { pairs, repeated }
+    }
+
+    fn eq_source(&self, source: &dyn kv::Source) -> bool {
+        struct Compare<'a>(::std::slice::Iter<'a, (String, kv::value::OwnedValue)>);
+
+        impl<'a, 'kvs> kv::Visitor<'kvs> for Compare<'a> {
+            fn visit_pair(
+                &mut self,
+                key: kv::Key<'kvs>,
+                value: kv::Value<'kvs>,
+            ) -> Result<(), kv::Error> {
+                match self.0.next() {
+                    Some((k, v)) if k == key.as_str() && *v == value.to_owned_value() => Ok(()),
+                    _ => Err(kv::Error::msg("the pairs are different")),
+                }
+            }
+        }
+
+        let mut compare = Compare(self.pairs.iter());
+        source.visit(&mut compare).is_ok() && compare.0.next().is_none()
+    }
+}
+
+#[cfg(feature = "kv_unstable")]
+impl kv::Source for OwnedKeyValues {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn kv::Visitor<'kvs>) -> Result<(), kv::Error> {
+        for (key, value) in &self.pairs {
+            visitor.visit_pair(kv::Key::from_str(key), kv::ToValue::to_value(value))?;
+        }
+
+        Ok(())
+    }
+
+    fn get<'v>(&'v self, key: kv::Key) -> Option<kv::Value<'v>> {
+        if let Some((_, value)) = self.repeated.iter().find(|(k, _)| k == key.as_str()) {
+            return value.as_ref().map(kv::ToValue::to_value);
+        }
+
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key.as_str())
+            .map(|(_, value)| kv::ToValue::to_value(value))
+    }
+
+    fn count(&self) -> usize {
+        self.pairs.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "kv_unstable")]
+    use kv::value::tests::Token;
+
+    #[test]
+    fn owned_record_round_trip() {
+        let owned = OwnedRecord::from_record(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("myApp")
+                .args(format_args!("hello {}", "world"))
+                .module_path(Some("foo"))
+                .file(Some("bar"))
+                .line(Some(30))
+                .build(),
+        );
+
+        assert_eq!(Level::Warn, owned.level());
+        assert_eq!("myApp", owned.target());
+        assert_eq!("hello world", owned.message());
+        assert_eq!(Some("foo"), owned.module_path());
+        assert_eq!(Some("bar"), owned.file());
+        assert_eq!(Some(30), owned.line());
+
+        owned.with_record(|record| {
+            assert_eq!("hello world", record.args().to_string());
+            assert_eq!(Some(30), record.line());
+        });
+    }
+
+    #[test]
+    fn owned_record_with_message() {
+        let owned = OwnedRecord::from_record(&Record::builder().level(Level::Info).build())
+            .with_message(format!("hello {}", "world"));
+
+        assert_eq!("hello world", owned.message());
+        owned.with_record(|record| assert_eq!("hello world", record.args().to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable")]
+    fn owned_record_key_values() {
+        let kvs: &[(&str, i32)] = &[("a", 1), ("b", 2)];
+        let owned = OwnedRecord::from_record(&Record::builder().key_values(&kvs).build());
+
+        assert_eq!(2, owned.key_values().count());
+        assert_eq!(
+            Token::I64(2),
+            owned.key_values().get("b".into()).unwrap().to_token()
+        );
+
+        owned.with_record(|record| assert_eq!(2, record.key_values().count()));
+    }
+
+    #[test]
+    #[cfg(feature = "kv_unstable")]
+    fn owned_record_repeated_keys() {
+        struct LastWins<'a>(&'a [(&'a str, i32)]);
+
+        impl<'a> kv::Source for LastWins<'a> {
+            fn visit<'kvs>(
+                &'kvs self,
+                visitor: &mut dyn kv::Visitor<'kvs>,
+            ) -> Result<(), kv::Error> {
+                self.0.visit(visitor)
+            }
+
+            fn get<'v>(&'v self, key: kv::Key) -> Option<kv::Value<'v>> {
+                self.0
+                    .iter()
+                    .rev()
+                    .find(|(k, _)| *k == key.as_str())
+                    .map(|(_, value)| kv::Value::from(*value))
+            }
+
+            fn count(&self) -> usize {
+                self.0.len()
+            }
+        }
+
+        let kvs = LastWins(&[("a", 1), ("a", 2)]);
+        let owned = OwnedRecord::from_record(&Record::builder().key_values(&kvs).build());
+
+        assert_eq!(2, owned.key_values().count());
+        assert_eq!(
+            Token::I64(2),
+            owned.key_values().get("a".into()).unwrap().to_token()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! A logger that panics when errors are logged.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use {Level, Log, Metadata, Record};
+
+/// A logger that panics when an `Error` record is logged.
+///
+/// Records are forwarded to the inner logger first, so the error is still
+/// captured before the panic. This is useful in test suites that should fail
+/// fast if anything unexpectedly logs an error. The logger starts armed, and
+/// can be disarmed with [`disarm`](#method.disarm) around code that's
+/// expected to log errors.
+///
+/// Requires the `std` feature.
+#[derive(Debug)]
+pub struct PanicOnError<L> {
+    inner: L,
+    armed: AtomicBool,
+}
+
+impl<L> PanicOnError<L>
+where
+    L: Log,
+{
+    /// Wrap a logger.
+    pub fn new(inner: L) -> Self {
+        PanicOnError {
+            inner,
+            armed: AtomicBool::new(true),
+        }
+    }
+
+    /// Panic when `Error` records are logged.
+    pub fn arm(&self) {
+        self.armed.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop panicking when `Error` records are logged.
+    pub fn disarm(&self) {
+        self.armed.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether `Error` records will cause a panic.
+    pub fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::Relaxed)
+    }
+}
+
+impl<L> Log for PanicOnError<L>
+where
+    L: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner.log(record);
+
+        if record.level() == Level::Error && self.is_armed() {
+            panic!("an error was logged: {}", record.args());
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic;
+
+    struct Discard;
+
+    impl Log for Discard {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, _: &Record) {}
+
+        fn flush(&self) {}
+    }
+
+    fn log(logger: &PanicOnError<Discard>, level: Level) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .args(format_args!("something happened"))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn panic_on_error() {
+        let logger = PanicOnError::new(Discard);
+
+        assert!(panic::catch_unwind(|| log(&logger, Level::Warn)).is_ok());
+
+        let err = panic::catch_unwind(|| log(&logger, Level::Error)).unwrap_err();
+        assert_eq!(
+            Some(&"an error was logged: something happened".to_owned()),
+            err.downcast_ref::<String>()
+        );
+
+        logger.disarm();
+        assert!(panic::catch_unwind(|| log(&logger, Level::Error)).is_ok());
+    }
+}
@@ -0,0 +1,133 @@
+//! A logger that limits how often records are forwarded.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use {Log, Metadata, Record};
+
+/// A logger that forwards at most a fixed number of records in each period.
+///
+/// The first record starts a period, and records beyond the limit are dropped
+/// until the period has elapsed. Only records enabled by the inner logger
+/// count towards the limit.
+///
+/// Requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use log::RateLimited;
+/// # struct MyLogger;
+/// # impl log::Log for MyLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, _: &log::Record) {}
+/// #     fn flush(&self) {}
+/// # }
+///
+/// // Forward at most 100 records a second
+/// let logger = RateLimited::new(MyLogger, 100, Duration::from_secs(1));
+/// ```
+#[derive(Debug)]
+pub struct RateLimited<L> {
+    inner: L,
+    limit: u32,
+    period: Duration,
+    window: Mutex<Option<(Instant, u32)>>,
+}
+
+impl<L> RateLimited<L>
+where
+    L: Log,
+{
+    /// Wrap a logger, forwarding at most `limit` records every `period`.
+    pub fn new(inner: L, limit: u32, period: Duration) -> Self {
+        RateLimited {
+            inner,
+            limit,
+            period,
+            window: Mutex::new(None),
+        }
+    }
+
+    fn acquire(&self) -> bool {
+        let now = Instant::now();
+        let mut window = self.window.lock().unwrap_or_else(|err| err.into_inner());
+
+        match *window {
+            Some((start, ref mut count)) if now.duration_since(start) < self.period => {
+                if *count < self.limit {
+                    *count += 1;
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => {
+                *window = Some((now, 1));
+                self.limit > 0
+            }
+        }
+    }
+}
+
+impl<L> Log for RateLimited<L>
+where
+    L: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.enabled(record.metadata()) && self.acquire() {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Count(AtomicUsize);
+
+    impl Log for Count {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, _: &Record) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn rate_limited() {
+        let logger = RateLimited::new(Count(AtomicUsize::new(0)), 2, Duration::from_secs(3600));
+
+        for _ in 0..5 {
+            logger.log(&Record::builder().build());
+        }
+
+        assert_eq!(2, logger.inner.0.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn rate_limited_starts_a_new_period() {
+        let logger = RateLimited::new(Count(AtomicUsize::new(0)), 1, Duration::from_secs(0));
+
+        for _ in 0..3 {
+            logger.log(&Record::builder().build());
+        }
+
+        assert_eq!(3, logger.inner.0.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,150 @@
+//! A global logger that can be replaced after it's installed.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+#[cfg(atomic_cas)]
+use {set_boxed_logger, SetLoggerError};
+use {Log, Metadata, Record};
+
+/// Sets the global logger to one that can be replaced later.
+///
+/// The global logger can only be set once, so this installs a wrapper that
+/// forwards records to `initial`, and returns a
+/// [`ReloadHandle`](struct.ReloadHandle.html) that can swap in a new logger
+/// at any time. Each record is logged while holding a read lock on the current
+/// logger, so reloading waits for in-flight records to finish.
+///
+/// Requires the `std` feature.
+///
+/// # Errors
+///
+/// An error is returned if a logger has already been set.
+///
+/// # Examples
+///
+/// ```
+/// # struct MyLogger;
+/// # impl log::Log for MyLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, _: &log::Record) {}
+/// #     fn flush(&self) {}
+/// # }
+/// let handle = log::init_reloadable(Box::new(MyLogger)).unwrap();
+///
+/// // Later, when the configuration changes
+/// handle.reload(Box::new(MyLogger));
+/// ```
+#[cfg(atomic_cas)]
+pub fn init_reloadable(initial: Box<dyn Log>) -> Result<ReloadHandle, SetLoggerError> {
+    let (logger, handle) = reloadable(initial);
+
+    set_boxed_logger(Box::new(logger)).map(|()| handle)
+}
+
+/// A handle to replace the logger installed by
+/// [`init_reloadable`](fn.init_reloadable.html).
+///
+/// Requires the `std` feature.
+#[derive(Clone)]
+pub struct ReloadHandle {
+    current: Arc<RwLock<Box<dyn Log>>>,
+}
+
+impl ReloadHandle {
+    /// Replace the current logger.
+    ///
+    /// The previous logger is flushed before it's dropped.
+    pub fn reload(&self, new: Box<dyn Log>) {
+        let previous = {
+            let mut current = self.current.write().unwrap_or_else(|err| err.into_inner());
+
+            std::mem::replace(&mut *current, new)
+        };
+
+        previous.flush();
+    }
+}
+
+impl fmt::Debug for ReloadHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReloadHandle").finish()
+    }
+}
+
+struct Reloadable {
+    current: Arc<RwLock<Box<dyn Log>>>,
+}
+
+fn reloadable(initial: Box<dyn Log>) -> (Reloadable, ReloadHandle) {
+    let current = Arc::new(RwLock::new(initial));
+
+    (
+        Reloadable {
+            current: current.clone(),
+        },
+        ReloadHandle { current },
+    )
+}
+
+impl Log for Reloadable {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.current
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.current
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .log(record)
+    }
+
+    fn flush(&self) {
+        self.current
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct Capture(&'static str, Arc<Mutex<Vec<String>>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.1
+                .lock()
+                .unwrap()
+                .push(format!("{}: {}", self.0, record.args()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn log(logger: &Reloadable, message: &str) {
+        logger.log(&Record::builder().args(format_args!("{}", message)).build());
+    }
+
+    #[test]
+    fn reload_replaces_logger() {
+        let logged = Arc::new(Mutex::new(Vec::new()));
+        let (logger, handle) = reloadable(Box::new(Capture("first", logged.clone())));
+
+        log(&logger, "a");
+        handle.reload(Box::new(Capture("second", logged.clone())));
+        log(&logger, "b");
+
+        assert_eq!(vec!["first: a", "second: b"], *logged.lock().unwrap());
+    }
+}
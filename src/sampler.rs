@@ -0,0 +1,222 @@
+//! A logger that samples records.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "kv_unstable")]
+use kv;
+use {Level, Log, Metadata, Record};
+
+/// A logger that only forwards a random sample of records to another logger.
+///
+/// Each record is forwarded with a probability given by the rate for its level,
+/// from `0.0` to drop every record up to `1.0` to keep every record. Sampling
+/// happens after the inner logger's `enabled` check, so records that would be
+/// filtered out anyway don't affect the sample.
+///
+/// With the `kv_unstable` feature, forwarded records have a `sampled` key set
+/// to `true`, and a `sample_weight` key with the number of records each one
+/// stands for, which is `1 / rate`.
+///
+/// Random numbers come from a small deterministic generator local to each thread,
+/// so the sample isn't suitable for anything that needs real randomness.
+///
+/// Requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use log::{Level, Sampler};
+/// # struct MyLogger;
+/// # impl log::Log for MyLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, _: &log::Record) {}
+/// #     fn flush(&self) {}
+/// # }
+///
+/// // Keep every warning and error, but only 1 in 100 trace records
+/// let logger = Sampler::new(MyLogger, 1.0).level_rate(Level::Trace, 0.01);
+/// ```
+#[derive(Debug)]
+pub struct Sampler<L> {
+    inner: L,
+    rates: [f64; 6],
+}
+
+impl<L> Sampler<L>
+where
+    L: Log,
+{
+    /// Wrap a logger, forwarding records at any level with the given rate.
+    pub fn new(inner: L, rate: f64) -> Self {
+        Sampler {
+            inner,
+            rates: [rate; 6],
+        }
+    }
+
+    /// Set the rate to forward records at the given level with.
+    pub fn level_rate(mut self, level: Level, rate: f64) -> Self {
+        self.rates[level as usize] = rate;
+        self
+    }
+
+    #[cfg(feature = "kv_unstable")]
+    fn forward(&self, record: &Record, rate: f64) {
+        let sampled = Sampled {
+            record: record.key_values(),
+            weight: 1.0 / rate,
+        };
+
+        self.inner
+            .log(&record.to_builder().key_values(&sampled).build());
+    }
+
+    #[cfg(not(feature = "kv_unstable"))]
+    fn forward(&self, record: &Record, _: f64) {
+        self.inner.log(record);
+    }
+}
+
+impl<L> Log for Sampler<L>
+where
+    L: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.inner.enabled(record.metadata()) {
+            return;
+        }
+
+        let rate = self.rates[record.level() as usize];
+        if rate >= 1.0 || (rate > 0.0 && next_f64() < rate) {
+            self.forward(record, rate);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+// Seeds are spread out between threads so they don't all produce
+// the same sequence of numbers.
+static NEXT_SEED: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static RNG: Cell<u64> = Cell::new(
+        (NEXT_SEED.fetch_add(1, Ordering::Relaxed) as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1
+    );
+}
+
+// A xorshift64* generator producing numbers in `[0, 1)`.
+fn next_f64() -> f64 {
+    RNG.with(|rng| {
+        let mut x = rng.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        rng.set(x);
+
+        (x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
+#[cfg(feature = "kv_unstable")]
+struct Sampled<'a> {
+    record: &'a dyn kv::Source,
+    weight: f64,
+}
+
+#[cfg(feature = "kv_unstable")]
+impl<'a> kv::Source for Sampled<'a> {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn kv::Visitor<'kvs>) -> Result<(), kv::Error> {
+        self.record.visit(visitor)?;
+        visitor.visit_pair(kv::Key::from_str("sampled"), kv::Value::from(true))?;
+        visitor.visit_pair(
+            kv::Key::from_str("sample_weight"),
+            kv::Value::from(self.weight),
+        )
+    }
+
+    fn get<'v>(&'v self, key: kv::Key) -> Option<kv::Value<'v>> {
+        match key.as_str() {
+            "sampled" => Some(kv::Value::from(true)),
+            "sample_weight" => Some(kv::Value::from(self.weight)),
+            _ => self.record.get(key),
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.record.count() + 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct Capture(Mutex<Vec<Level>>);
+
+    impl Log for Capture {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Debug
+        }
+
+        fn log(&self, record: &Record) {
+            #[cfg(feature = "kv_unstable")]
+            assert_eq!(
+                Some(true),
+                record
+                    .key_values()
+                    .get(kv::Key::from_str("sampled"))
+                    .and_then(|sampled| sampled.to_bool())
+            );
+
+            self.0.lock().unwrap().push(record.level());
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn sample(logger: Sampler<Capture>) -> Vec<usize> {
+        for _ in 0..100 {
+            for &level in &[Level::Error, Level::Info, Level::Trace] {
+                logger.log(&Record::builder().level(level).build());
+            }
+        }
+
+        let logged = logger.inner.0.lock().unwrap();
+        [Level::Error, Level::Info, Level::Trace]
+            .iter()
+            .map(|level| logged.iter().filter(|logged| *logged == level).count())
+            .collect()
+    }
+
+    fn capture() -> Capture {
+        Capture(Mutex::new(Vec::new()))
+    }
+
+    #[test]
+    fn sampler_rate() {
+        // Trace records are never enabled by the inner logger
+        assert_eq!(vec![100, 100, 0], sample(Sampler::new(capture(), 1.0)));
+        assert_eq!(vec![0, 0, 0], sample(Sampler::new(capture(), 0.0)));
+        assert_eq!(
+            vec![100, 0, 0],
+            sample(Sampler::new(capture(), 0.0).level_rate(Level::Error, 1.0))
+        );
+    }
+
+    #[test]
+    fn next_f64_in_range() {
+        for _ in 0..1000 {
+            let x = next_f64();
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! A logger that stamps records with a schema version.
+
+use kv::source::ContextOverlay;
+#[cfg(atomic_cas)]
+use {set_boxed_logger, SetLoggerError};
+use {Log, Metadata, Record};
+
+const SCHEMA_VERSION_KEY: &str = "schema.version";
+
+/// A logger that adds a `schema.version` field to records before forwarding
+/// them to another logger.
+///
+/// Log pipelines can use the field to tell which version of a schema a record
+/// was written with. The field is visited before any of the record's own
+/// key-value pairs.
+///
+/// Requires the `std` and `kv_unstable` features.
+///
+/// # Examples
+///
+/// ```
+/// use log::SchemaLogger;
+/// # struct MyLogger;
+/// # impl log::Log for MyLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, _: &log::Record) {}
+/// #     fn flush(&self) {}
+/// # }
+///
+/// let logger = SchemaLogger::new(MyLogger, "1.2");
+/// ```
+#[derive(Debug)]
+pub struct SchemaLogger<L> {
+    inner: L,
+    version: &'static str,
+}
+
+impl<L> SchemaLogger<L>
+where
+    L: Log,
+{
+    /// Wrap a logger, adding the given schema version to its records.
+    pub fn new(inner: L, version: &'static str) -> Self {
+        SchemaLogger { inner, version }
+    }
+}
+
+impl<L> Log for SchemaLogger<L>
+where
+    L: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let schema = (SCHEMA_VERSION_KEY, self.version);
+        let key_values = ContextOverlay::new(record.key_values(), &schema);
+
+        self.inner
+            .log(&record.to_builder().key_values(&key_values).build());
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+/// Sets the global logger to `inner`, adding a `schema.version` field to
+/// every record it's given.
+///
+/// This is the same as calling [`set_boxed_logger`](fn.set_boxed_logger.html)
+/// with a [`SchemaLogger`](struct.SchemaLogger.html).
+///
+/// Requires the `std` and `kv_unstable` features.
+///
+/// # Errors
+///
+/// An error is returned if a logger has already been set.
+#[cfg(atomic_cas)]
+pub fn init_with_schema<L>(inner: L, version: &'static str) -> Result<(), SetLoggerError>
+where
+    L: Log + 'static,
+{
+    set_boxed_logger(Box::new(SchemaLogger::new(inner, version)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kv::Key;
+    use std::sync::Mutex;
+    use Level;
+
+    struct Capture(Mutex<Vec<Option<String>>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push(
+                record
+                    .key_values()
+                    .get(Key::from_str(SCHEMA_VERSION_KEY))
+                    .map(|version| version.to_string()),
+            );
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn schema_logger_adds_version() {
+        let logger = SchemaLogger::new(Capture(Mutex::new(Vec::new())), "1.2");
+
+        logger.log(&Record::builder().level(Level::Info).build());
+        logger.log(
+            &Record::builder()
+                .level(Level::Warn)
+                .key_values(&("user", "alice"))
+                .build(),
+        );
+
+        assert_eq!(
+            vec![Some("1.2".to_owned()), Some("1.2".to_owned())],
+            *logger.inner.0.lock().unwrap()
+        );
+    }
+}
@@ -7,7 +7,7 @@ use self::serde::de::{
 };
 use self::serde::ser::{Serialize, Serializer};
 
-use {Level, LevelFilter, LOG_LEVEL_NAMES};
+use {Level, LevelFilter, LowercaseLevel, NumericLevel, NumericLevelFilter, LOG_LEVEL_NAMES};
 
 use std::fmt;
 use std::str::{self, FromStr};
@@ -29,6 +29,21 @@ impl Serialize for Level {
     }
 }
 
+impl Serialize for LowercaseLevel {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            Level::Error => serializer.serialize_unit_variant("Level", 0, "error"),
+            Level::Warn => serializer.serialize_unit_variant("Level", 1, "warn"),
+            Level::Info => serializer.serialize_unit_variant("Level", 2, "info"),
+            Level::Debug => serializer.serialize_unit_variant("Level", 3, "debug"),
+            Level::Trace => serializer.serialize_unit_variant("Level", 4, "trace"),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Level {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -203,12 +218,112 @@ impl<'de> Deserialize<'de> for LevelFilter {
     }
 }
 
+// The numeric wrappers serialize as plain integers.
+
+impl Serialize for NumericLevel {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u64(self.0 as u64)
+    }
+}
+
+impl<'de> Deserialize<'de> for NumericLevel {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NumericLevelVisitor;
+
+        impl<'de> Visitor<'de> for NumericLevelVisitor {
+            type Value = NumericLevel;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("log level number between 1 and 5")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Level::from_usize(v as usize)
+                    .map(NumericLevel)
+                    .ok_or_else(|| Error::invalid_value(Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                if v < 0 {
+                    return Err(Error::invalid_value(Unexpected::Signed(v), &self));
+                }
+
+                self.visit_u64(v as u64)
+            }
+        }
+
+        deserializer.deserialize_u64(NumericLevelVisitor)
+    }
+}
+
+impl Serialize for NumericLevelFilter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u64(self.0 as u64)
+    }
+}
+
+impl<'de> Deserialize<'de> for NumericLevelFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NumericLevelFilterVisitor;
+
+        impl<'de> Visitor<'de> for NumericLevelFilterVisitor {
+            type Value = NumericLevelFilter;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("log level filter number between 0 and 5")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                LevelFilter::from_usize(v as usize)
+                    .map(NumericLevelFilter)
+                    .ok_or_else(|| Error::invalid_value(Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                if v < 0 {
+                    return Err(Error::invalid_value(Unexpected::Signed(v), &self));
+                }
+
+                self.visit_u64(v as u64)
+            }
+        }
+
+        deserializer.deserialize_u64(NumericLevelFilterVisitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     extern crate serde_test;
-    use self::serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+    use self::serde_test::{
+        assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, assert_tokens, Token,
+    };
 
-    use {Level, LevelFilter};
+    use {Level, LevelFilter, NumericLevel, NumericLevelFilter};
 
     fn level_token(variant: &'static str) -> Token {
         Token::UnitVariant {
@@ -275,6 +390,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_level_ser_lowercase() {
+        let cases = [
+            (Level::Error, [level_token("error")]),
+            (Level::Warn, [level_token("warn")]),
+            (Level::Info, [level_token("info")]),
+            (Level::Debug, [level_token("debug")]),
+            (Level::Trace, [level_token("trace")]),
+        ];
+
+        for &(s, expected) in &cases {
+            assert_ser_tokens(&s.as_serialize_lower(), &expected);
+        }
+    }
+
     #[test]
     fn test_level_case_insensitive() {
         let cases = [
@@ -397,4 +527,44 @@ mod tests {
                    `OFF`, `ERROR`, `WARN`, `INFO`, `DEBUG`, `TRACE`";
         assert_de_tokens_error::<LevelFilter>(&[level_filter_token("errorx")], msg);
     }
+
+    #[test]
+    fn test_numeric_level_ser_de() {
+        assert_tokens(&NumericLevel(Level::Info), &[Token::U64(3)]);
+        assert_tokens(&NumericLevel(Level::Error), &[Token::U64(1)]);
+        assert_tokens(&NumericLevel(Level::Trace), &[Token::U64(5)]);
+
+        assert_de_tokens(&NumericLevel(Level::Info), &[Token::U8(3)]);
+        assert_de_tokens(&NumericLevel(Level::Info), &[Token::I64(3)]);
+
+        assert_eq!(
+            NumericLevel(Level::Warn),
+            Level::Warn.as_serialize_numeric()
+        );
+        assert_eq!(Level::Warn, Level::Warn.as_serialize_numeric().level());
+    }
+
+    #[test]
+    fn test_numeric_level_de_error() {
+        let msg = "invalid value: integer `0`, expected log level number between 1 and 5";
+        assert_de_tokens_error::<NumericLevel>(&[Token::U64(0)], msg);
+    }
+
+    #[test]
+    fn test_numeric_level_filter_ser_de() {
+        assert_tokens(&NumericLevelFilter(LevelFilter::Off), &[Token::U64(0)]);
+        assert_tokens(&NumericLevelFilter(LevelFilter::Info), &[Token::U64(3)]);
+        assert_tokens(&NumericLevelFilter(LevelFilter::Trace), &[Token::U64(5)]);
+
+        assert_eq!(
+            LevelFilter::Off,
+            LevelFilter::Off.as_serialize_numeric().level_filter()
+        );
+    }
+
+    #[test]
+    fn test_numeric_level_filter_de_error() {
+        let msg = "invalid value: integer `6`, expected log level filter number between 0 and 5";
+        assert_de_tokens_error::<NumericLevelFilter>(&[Token::U64(6)], msg);
+    }
 }
@@ -0,0 +1,169 @@
+//! A logger that timestamps records.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use {Log, Metadata, Record};
+
+/// A source of the current time.
+///
+/// This lets the time used by a [`TimestampLogger`](struct.TimestampLogger.html)
+/// be swapped out, such as for a fixed clock in tests.
+///
+/// Requires the `std` feature.
+pub trait Clock {
+    /// The current time, as nanoseconds since the Unix epoch.
+    fn now_unix_nanos(&self) -> u64;
+}
+
+/// A [`Clock`](trait.Clock.html) that reads the system time.
+///
+/// Requires the `std` feature.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix_nanos(&self) -> u64 {
+        // Times before the epoch are clamped to it
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs() * 1_000_000_000 + u64::from(now.subsec_nanos()))
+            .unwrap_or(0)
+    }
+}
+
+/// A logger that sets the [`timestamp`](struct.Record.html#method.timestamp)
+/// of records before forwarding them to another logger.
+///
+/// The time is read from a [`Clock`](trait.Clock.html) when the record is logged.
+/// Records that already have a timestamp, such as ones replayed from an
+/// [`OwnedRecord`](struct.OwnedRecord.html), are forwarded unchanged.
+///
+/// Requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use log::TimestampLogger;
+/// # struct MyLogger;
+/// # impl log::Log for MyLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, _: &log::Record) {}
+/// #     fn flush(&self) {}
+/// # }
+///
+/// let logger = TimestampLogger::new(MyLogger);
+/// ```
+#[derive(Debug)]
+pub struct TimestampLogger<L, C = SystemClock> {
+    inner: L,
+    clock: C,
+}
+
+impl<L> TimestampLogger<L>
+where
+    L: Log,
+{
+    /// Wrap a logger, timestamping records with the system time.
+    pub fn new(inner: L) -> Self {
+        TimestampLogger::with_clock(inner, SystemClock)
+    }
+}
+
+impl<L, C> TimestampLogger<L, C>
+where
+    L: Log,
+    C: Clock,
+{
+    /// Wrap a logger, timestamping records with the given clock.
+    pub fn with_clock(inner: L, clock: C) -> Self {
+        TimestampLogger { inner, clock }
+    }
+
+    #[cfg(feature = "kv_unstable")]
+    fn forward(&self, record: &Record, timestamp: u64) {
+        self.inner
+            .log(&record.to_builder().timestamp(Some(timestamp)).build());
+    }
+
+    #[cfg(not(feature = "kv_unstable"))]
+    fn forward(&self, record: &Record, timestamp: u64) {
+        self.inner.log(
+            &Record::builder()
+                .metadata(record.metadata().clone())
+                .args(*record.args())
+                .module_path(record.module_path())
+                .file(record.file())
+                .line(record.line())
+                .timestamp(Some(timestamp))
+                .span_id(record.span_id())
+                .trace_id(record.trace_id())
+                .build(),
+        );
+    }
+}
+
+impl<L, C> Log for TimestampLogger<L, C>
+where
+    L: Log,
+    C: Clock + Send + Sync,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.timestamp().is_some() {
+            self.inner.log(record);
+        } else {
+            self.forward(record, self.clock.now_unix_nanos());
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct FixedClock(u64);
+
+    impl Clock for FixedClock {
+        fn now_unix_nanos(&self) -> u64 {
+            self.0
+        }
+    }
+
+    struct Capture(Mutex<Vec<Option<u64>>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push(record.timestamp());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn timestamp_logger_stamps_records() {
+        let logger =
+            TimestampLogger::with_clock(Capture(Mutex::new(Vec::new())), FixedClock(1_000));
+
+        logger.log(&Record::builder().build());
+        logger.log(&Record::builder().timestamp(Some(42)).build());
+
+        assert_eq!(vec![Some(1_000), Some(42)], *logger.inner.0.lock().unwrap());
+    }
+
+    #[test]
+    fn system_clock_is_after_epoch() {
+        assert!(SystemClock.now_unix_nanos() > 0);
+    }
+}
@@ -0,0 +1,101 @@
+//! A logger that adds key-value pairs to records.
+
+use kv::Source;
+use {Log, Metadata, Record};
+
+/// A logger that chains a fixed set of key-value pairs onto each record.
+///
+/// The record's own pairs are visited first, followed by the added ones.
+///
+/// Requires the `std` and `kv_unstable` features.
+///
+/// # Examples
+///
+/// ```
+/// use log::WithFields;
+/// # struct MyLogger;
+/// # impl log::Log for MyLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, _: &log::Record) {}
+/// #     fn flush(&self) {}
+/// # }
+///
+/// let logger = WithFields::new(MyLogger, vec![("service", "api"), ("region", "eu")]);
+/// ```
+#[derive(Debug)]
+pub struct WithFields<L, S> {
+    inner: L,
+    fields: S,
+}
+
+impl<L, S> WithFields<L, S>
+where
+    L: Log,
+    S: Source,
+{
+    /// Wrap a logger, adding the pairs of the given source to each record.
+    pub fn new(inner: L, fields: S) -> Self {
+        WithFields { inner, fields }
+    }
+}
+
+impl<L, S> Log for WithFields<L, S>
+where
+    L: Log,
+    S: Source + Send + Sync,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner
+            .log(&record.to_builder().chain_key_values(&self.fields).build());
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kv::source::as_map;
+    use std::sync::Mutex;
+    use Level;
+
+    struct Capture(Mutex<Vec<String>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{:?}", as_map(record.key_values())));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn with_fields() {
+        let logger = WithFields::new(Capture(Mutex::new(Vec::new())), ("service", "api"));
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .key_values(&("a", 1))
+                .build(),
+        );
+
+        assert_eq!(
+            vec![r#"{"a": 1, "service": "api"}"#],
+            *logger.inner.0.lock().unwrap()
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! A logger that writes formatted records to an `io::Write`.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use {DisplayOptions, Log, Metadata, Record};
+
+/// The function used to format records by default.
+type DefaultFormat = fn(&Record, &mut dyn Write) -> io::Result<()>;
+
+/// A logger that formats records and writes them to an `io::Write`.
+///
+/// By default, each record is written on its own line using
+/// [`Record::display_with`](struct.Record.html#method.display_with) with the
+/// default [`DisplayOptions`](struct.DisplayOptions.html). A custom format
+/// can be given with [`with_format`](#method.with_format).
+///
+/// The writer is guarded by a mutex, so records from different threads don't
+/// interleave. Errors writing records are ignored.
+///
+/// Requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+/// use log::WriteLogger;
+///
+/// let logger = WriteLogger::with_format(Vec::new(), |record, writer| {
+///     writeln!(writer, "[{}] {}", record.level(), record.args())
+/// });
+/// ```
+pub struct WriteLogger<W, F = DefaultFormat> {
+    writer: Mutex<W>,
+    format: F,
+}
+
+impl<W> WriteLogger<W>
+where
+    W: Write + Send,
+{
+    /// Write records to the given writer using the default format.
+    pub fn new(writer: W) -> Self {
+        WriteLogger::with_format(writer, format_record as DefaultFormat)
+    }
+}
+
+impl WriteLogger<io::Stderr> {
+    /// Write records to the standard error stream using the default format.
+    pub fn stderr() -> Self {
+        WriteLogger::new(io::stderr())
+    }
+}
+
+impl WriteLogger<io::Stdout> {
+    /// Write records to the standard output stream using the default format.
+    pub fn stdout() -> Self {
+        WriteLogger::new(io::stdout())
+    }
+}
+
+impl<W, F> WriteLogger<W, F>
+where
+    W: Write + Send,
+    F: Fn(&Record, &mut dyn Write) -> io::Result<()>,
+{
+    /// Write records to the given writer using a custom format.
+    pub fn with_format(writer: W, format: F) -> Self {
+        WriteLogger {
+            writer: Mutex::new(writer),
+            format,
+        }
+    }
+
+    /// Get back the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn format_record(record: &Record, writer: &mut dyn Write) -> io::Result<()> {
+    writeln!(writer, "{}", record.display_with(DisplayOptions::new()))
+}
+
+impl<W, F> fmt::Debug for WriteLogger<W, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WriteLogger").finish()
+    }
+}
+
+impl<W, F> Log for WriteLogger<W, F>
+where
+    W: Write + Send,
+    F: Fn(&Record, &mut dyn Write) -> io::Result<()> + Send + Sync,
+{
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let mut writer = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+        let _ = (self.format)(record, &mut *writer);
+    }
+
+    fn flush(&self) {
+        let _ = self
+            .writer
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Level;
+
+    #[test]
+    fn write_logger_default_format() {
+        let logger = WriteLogger::new(Vec::new());
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .target("myApp")
+                .args(format_args!("started"))
+                .build(),
+        );
+        logger.log(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("myApp")
+                .args(format_args!("slow"))
+                .build(),
+        );
+
+        assert_eq!(
+            "INFO myApp: started\nWARN myApp: slow\n",
+            String::from_utf8(logger.into_inner()).unwrap()
+        );
+    }
+
+    #[test]
+    fn write_logger_custom_format() {
+        let logger = WriteLogger::with_format(Vec::new(), |record, writer| {
+            writeln!(writer, "[{}] {}", record.level(), record.args())
+        });
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Error)
+                .args(format_args!("failed"))
+                .build(),
+        );
+
+        assert_eq!(
+            "[ERROR] failed\n",
+            String::from_utf8(logger.into_inner()).unwrap()
+        );
+    }
+}
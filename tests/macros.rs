@@ -35,3 +35,137 @@ fn with_named_args() {
     info!("hello {cats}", cats = cats,);
     info!("hello {cats}", cats = cats,);
 }
+
+// The `filters` tests set the global max level too, and share
+// a process with these ones when built as part of the test library.
+#[cfg(not(lib_build))]
+#[test]
+fn try_log() {
+    log::set_max_level(log::LevelFilter::Info);
+
+    assert!(try_log!(log::Level::Info, "hello"));
+    assert!(try_log!(target: "cats", log::Level::Warn, "hello {}", "cats"));
+    assert!(!try_log!(log::Level::Debug, "hello {}", "cats"));
+}
+
+// A global logger for tests of the key-value macros. Other tests in this
+// process log through the same logger, so records are kept by target.
+#[cfg(all(not(lib_build), feature = "kv_unstable", feature = "std"))]
+mod capture {
+    use log::kv::{self, Key};
+    use std::sync::{Mutex, Once};
+
+    struct Capture(Mutex<Vec<(String, String)>>);
+
+    impl log::Log for Capture {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            struct Pairs<'a, 'r>(&'a mut Vec<String>, &'r dyn kv::Source);
+
+            impl<'a, 'r, 'kvs> kv::Visitor<'kvs> for Pairs<'a, 'r> {
+                fn visit_pair(
+                    &mut self,
+                    key: Key<'kvs>,
+                    value: kv::Value<'kvs>,
+                ) -> Result<(), kv::Error> {
+                    assert!(self.1.get(key.clone()).is_some());
+
+                    self.0.push(format!("{}={}", key, value));
+                    Ok(())
+                }
+            }
+
+            let mut logged = vec![record.args().to_string()];
+            record
+                .key_values()
+                .visit(&mut Pairs(&mut logged, record.key_values()))
+                .unwrap();
+
+            let target = record.target().to_owned();
+            self.0
+                .lock()
+                .unwrap()
+                .extend(logged.into_iter().map(|line| (target.clone(), line)));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Capture = Capture(Mutex::new(Vec::new()));
+    static INIT: Once = Once::new();
+
+    /// Install the logger, if it hasn't been already.
+    pub fn init() {
+        INIT.call_once(|| {
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(log::LevelFilter::Info);
+        });
+    }
+
+    /// The messages and key-value pairs logged for a target.
+    pub fn logged(target: &str) -> Vec<String> {
+        LOGGER
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(logged, _)| logged == target)
+            .map(|(_, line)| line.clone())
+            .collect()
+    }
+}
+
+#[cfg(all(not(lib_build), feature = "kv_unstable", feature = "std"))]
+#[test]
+fn log_captured() {
+    capture::init();
+
+    let mut attempts = 2;
+    log_captured!(
+        target: "log_captured",
+        log::Level::Warn,
+        "login failed for {} after {} attempts",
+        "alice",
+        {
+            attempts += 1;
+            attempts
+        },
+    );
+
+    assert_eq!(3, attempts);
+    assert_eq!(
+        vec![
+            "login failed for alice after 3 attempts",
+            "arg.0=alice",
+            "arg.1=3"
+        ],
+        capture::logged("log_captured")
+    );
+}
+
+#[cfg(all(not(lib_build), feature = "kv_unstable", feature = "std"))]
+#[test]
+fn log_template() {
+    capture::init();
+
+    log_template!(
+        target: "log_template",
+        log::Level::Info,
+        "user {} logged in",
+        "alice"
+    );
+    log_template!(target: "log_template", log::Level::Info, "done");
+
+    assert_eq!(
+        vec![
+            "user alice logged in",
+            "message.template=user {} logged in",
+            "done",
+            "message.template=done"
+        ],
+        capture::logged("log_template")
+    );
+}